@@ -0,0 +1,195 @@
+//! Compares joined iteration over the per-component storages and the archetype backend
+//!
+//! Run with `cargo bench --bench storage`.
+
+extern crate hikari;
+
+use hikari::component::{Component, Position, Velocity};
+use hikari::query::{Join, TableJoin};
+use hikari::storage::map::MapStorage;
+use hikari::storage::sparse::SparseSetStorage;
+use hikari::world::{StorageBackend, World};
+
+use std::hint::black_box;
+use std::time::Instant;
+
+const ENTITIES: usize = 10_000;
+const ITERATIONS: u32 = 200;
+
+/// A pair of coordinates that can be stepped by another
+trait Kinematic: Component {
+    type Other: Kinematic;
+
+    fn new(x: f64, y: f64) -> Self;
+    fn step(&mut self, other: &Self::Other);
+}
+
+/// A position kept in a `MapStorage`
+struct MapPosition(f64, f64);
+
+/// A velocity kept in a `MapStorage`
+struct MapVelocity(f64, f64);
+
+/// A position kept in a `SparseSetStorage`
+struct SparsePosition(f64, f64);
+
+/// A velocity kept in a `SparseSetStorage`
+struct SparseVelocity(f64, f64);
+
+/// A component only some entities have, which splits them into more archetypes
+struct Marker;
+
+impl Component for MapPosition {
+    type Storage = MapStorage<Self>;
+}
+
+impl Component for MapVelocity {
+    type Storage = MapStorage<Self>;
+}
+
+impl Component for SparsePosition {
+    type Storage = SparseSetStorage<Self>;
+}
+
+impl Component for SparseVelocity {
+    type Storage = SparseSetStorage<Self>;
+}
+
+impl Component for Marker {
+    type Storage = MapStorage<Self>;
+}
+
+impl Kinematic for Position {
+    type Other = Velocity;
+
+    fn new(x: f64, y: f64) -> Self {
+        Position(x, y)
+    }
+
+    fn step(&mut self, velocity: &Velocity) {
+        self.0 += velocity.0;
+        self.1 += velocity.1;
+    }
+}
+
+impl Kinematic for Velocity {
+    type Other = Velocity;
+
+    fn new(x: f64, y: f64) -> Self {
+        Velocity(x, y)
+    }
+
+    fn step(&mut self, _: &Velocity) {}
+}
+
+impl Kinematic for MapPosition {
+    type Other = MapVelocity;
+
+    fn new(x: f64, y: f64) -> Self {
+        MapPosition(x, y)
+    }
+
+    fn step(&mut self, velocity: &MapVelocity) {
+        self.0 += velocity.0;
+        self.1 += velocity.1;
+    }
+}
+
+impl Kinematic for MapVelocity {
+    type Other = MapVelocity;
+
+    fn new(x: f64, y: f64) -> Self {
+        MapVelocity(x, y)
+    }
+
+    fn step(&mut self, _: &MapVelocity) {}
+}
+
+impl Kinematic for SparsePosition {
+    type Other = SparseVelocity;
+
+    fn new(x: f64, y: f64) -> Self {
+        SparsePosition(x, y)
+    }
+
+    fn step(&mut self, velocity: &SparseVelocity) {
+        self.0 += velocity.0;
+        self.1 += velocity.1;
+    }
+}
+
+impl Kinematic for SparseVelocity {
+    type Other = SparseVelocity;
+
+    fn new(x: f64, y: f64) -> Self {
+        SparseVelocity(x, y)
+    }
+
+    fn step(&mut self, _: &SparseVelocity) {}
+}
+
+/// Fills a world where every entity has a position, every other one a velocity and every tenth
+/// a marker
+fn world<P: Kinematic>(backend: StorageBackend) -> World {
+    let mut world = World::with_backend(backend);
+    world.register::<P>();
+    world.register::<P::Other>();
+    world.register::<Marker>();
+
+    for index in 0..ENTITIES {
+        let mut builder = world.create_entity().with_component(P::new(index as f64, 0.0));
+        if index % 2 == 0 {
+            builder = builder.with_component(P::Other::new(1.0, 1.0));
+        }
+
+        if index % 10 == 0 {
+            builder = builder.with_component(Marker);
+        }
+
+        builder.build();
+    }
+
+    world
+}
+
+/// Times moving each entity that has both a position and a velocity, joined entity by entity
+fn bench<P: Kinematic>(name: &str, backend: StorageBackend) {
+    let world = world::<P>(backend);
+    time(name, || {
+        for (_, (position, velocity)) in (&mut world.write::<P>(), &world.read::<P::Other>()).join() {
+            position.step(velocity);
+            black_box(position);
+        }
+    });
+}
+
+/// Times the same movement in an archetype world, joined a whole table at a time
+fn bench_tables<P: Kinematic>(name: &str) {
+    let world = world::<P>(StorageBackend::Archetype);
+    time(name, || {
+        for (_, (positions, velocities)) in (&mut world.write::<P>(), &world.read::<P::Other>()).join_tables() {
+            for (position, velocity) in positions.iter_mut().zip(velocities) {
+                position.step(velocity);
+                black_box(position);
+            }
+        }
+    });
+}
+
+fn time<F: FnMut()>(name: &str, mut iteration: F) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        iteration();
+    }
+
+    let elapsed = start.elapsed();
+    println!("{:<10} {:>10} ns/iteration", name, elapsed.as_nanos() / u128::from(ITERATIONS));
+}
+
+fn main() {
+    bench::<Position>("sequence", StorageBackend::PerComponent);
+    bench::<MapPosition>("map", StorageBackend::PerComponent);
+    bench::<SparsePosition>("sparse set", StorageBackend::PerComponent);
+    bench::<Position>("archetype", StorageBackend::Archetype);
+    bench_tables::<Position>("tables");
+}
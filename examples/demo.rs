@@ -0,0 +1,52 @@
+extern crate hikari;
+
+use hikari::app::App;
+use hikari::app::source::NoInput;
+use hikari::component::{Position, Velocity};
+use hikari::replay::Recording;
+
+use std::env;
+use std::process;
+
+fn main() {
+    let mut builder = App::builder();
+    builder.setup(|world| {
+        world.create_entity()
+            .with_component(Position(10.0, 10.0))
+            .with_component(Velocity(5.0, 1.0))
+            .make_player()
+            .build();
+    });
+
+    let mut app = builder.build().expect("demo systems should be schedulable");
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match (args.first().map(String::as_str), args.get(1)) {
+        (Some("--record"), Some(path)) => {
+            if let Err(error) = app.run_recorded(path) {
+                eprintln!("could not save recording: {}", error);
+                process::exit(1);
+            }
+        },
+        (Some("--replay"), Some(path)) => {
+            let recording = Recording::load(path).unwrap_or_else(|error| {
+                eprintln!("could not load recording: {}", error);
+                process::exit(1);
+            });
+
+            if let Err(divergence) = app.run_replay(&recording) {
+                eprintln!("{}", divergence);
+                process::exit(1);
+            }
+        },
+        (Some("--headless"), Some(ticks)) => {
+            let ticks = ticks.parse().unwrap_or_else(|_| {
+                eprintln!("expected a number of ticks, not `{}`", ticks);
+                process::exit(1);
+            });
+
+            app.run_ticks(&mut NoInput, ticks);
+        },
+        _ => app.run(),
+    }
+}
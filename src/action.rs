@@ -0,0 +1,385 @@
+use command::{Command, Commands};
+use input::{InputState, Keys};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The bindings used when no action map file is loaded
+pub const DEFAULT_BINDINGS: &str = "
+[gameplay]
+action quit = Escape
+action pause = P
+axis move_x = A / D, Left / Right
+axis move_y = S / W, Down / Up
+
+[menu]
+action confirm = Return, Space
+action cancel = Escape
+axis move_y = S / W, Down / Up
+";
+
+/// Analog inputs driven by pairs of opposing keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    MoveX,
+    MoveY,
+}
+
+impl Axis {
+    pub const COUNT: usize = Axis::MoveY as usize + 1;
+
+    /// Every axis, in index order
+    pub const ALL: [Axis; Axis::COUNT] = [Axis::MoveX, Axis::MoveY];
+
+    /// Looks an axis up by its lowercase name, as used in action map files
+    pub fn from_name(name: &str) -> Option<Axis> {
+        match name {
+            "move_x" => Some(Axis::MoveX),
+            "move_y" => Some(Axis::MoveY),
+            _ => None,
+        }
+    }
+}
+
+/// The current value of every axis, each between -1 and 1
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Axes([f64; Axis::COUNT]);
+
+impl Axes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, axis: Axis) -> f64 {
+        self.0[axis as usize]
+    }
+
+    /// Sets an axis, clamping the value between -1 and 1
+    pub fn set(&mut self, axis: Axis, value: f64) {
+        self.0[axis as usize] = value.clamp(-1.0, 1.0);
+    }
+}
+
+/// Keys that must all be held at once, such as `LControl+Q`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chord(Vec<Keys>);
+
+impl Chord {
+    pub fn new(keys: Vec<Keys>) -> Self {
+        Chord(keys)
+    }
+
+    pub fn is_held(&self, input: &InputState) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|key| input.held(*key))
+    }
+}
+
+/// A set of bindings that is active as a whole, such as those for gameplay or for a menu
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Context {
+    actions: Vec<(Command, Chord)>,
+    axes: Vec<(Axis, Chord, Chord)>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issues the command while the chord is held
+    pub fn bind_action(&mut self, command: Command, chord: Chord) {
+        self.actions.push((command, chord));
+    }
+
+    /// Pushes the axis towards -1 while `negative` is held and towards 1 while `positive` is
+    pub fn bind_axis(&mut self, axis: Axis, negative: Chord, positive: Chord) {
+        self.axes.push((axis, negative, positive));
+    }
+}
+
+/// Maps key chords to commands and axes, in named contexts that can be stacked at runtime
+///
+/// Only the context on top of the stack is active, so opening a menu over gameplay disables
+/// the gameplay controls until the menu context is popped.
+///
+/// Action map files are made of context headers followed by bindings. Several chords can be
+/// bound at once by separating them with commas, and `#` starts a comment:
+///
+/// ```text
+/// [gameplay]
+/// action quit = Escape, LControl+Q
+/// axis move_x = A / D, Left / Right
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionMap {
+    contexts: HashMap<String, Context>,
+    stack: Vec<String>,
+}
+
+impl ActionMap {
+    /// An action map without any contexts
+    pub fn new() -> Self {
+        ActionMap { contexts: HashMap::new(), stack: Vec::new() }
+    }
+
+    /// Parses an action map file, activating the first context it declares
+    pub fn parse(text: &str) -> Result<Self, ActionMapError> {
+        let mut map = ActionMap::new();
+        let mut current: Option<String> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| ActionMapError::Parse { line: index + 1, message };
+            let line = line.split('#').next().unwrap_or("").trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len() - 1].trim().to_owned();
+                if name.is_empty() {
+                    return Err(error("context header without a name".to_owned()));
+                }
+
+                if map.stack.is_empty() {
+                    map.stack.push(name.clone());
+                }
+
+                map.context_mut(&name);
+                current = Some(name);
+                continue;
+            }
+
+            let context = match current {
+                Some(ref name) => map.contexts.get_mut(name).unwrap(),
+                None => return Err(error("binding appears before any [context] header".to_owned())),
+            };
+
+            let (binding, chords) = match line.find('=') {
+                Some(position) => (line[..position].trim(), line[position + 1..].trim()),
+                None => return Err(error(format!("expected `=` in `{}`", line))),
+            };
+
+            let mut words = binding.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("action"), Some(name), None) => {
+                    let command = Command::from_name(name).ok_or_else(|| error(format!("unknown command `{}`", name)))?;
+                    for chord in chords.split(',') {
+                        context.bind_action(command, parse_chord(chord).map_err(&error)?);
+                    }
+                },
+                (Some("axis"), Some(name), None) => {
+                    let axis = Axis::from_name(name).ok_or_else(|| error(format!("unknown axis `{}`", name)))?;
+                    for pair in chords.split(',') {
+                        let mut halves = pair.split('/');
+                        match (halves.next(), halves.next(), halves.next()) {
+                            (Some(negative), Some(positive), None) => {
+                                let negative = parse_chord(negative).map_err(&error)?;
+                                let positive = parse_chord(positive).map_err(&error)?;
+                                context.bind_axis(axis, negative, positive);
+                            },
+                            _ => return Err(error(format!("expected `negative / positive` in `{}`", pair.trim()))),
+                        }
+                    }
+                },
+                _ => return Err(error(format!("expected `action <name>` or `axis <name>` in `{}`", binding))),
+            }
+        }
+
+        Ok(map)
+    }
+
+    /// Reads and parses an action map file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ActionMapError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The context of the given name, created empty if it does not exist
+    pub fn context_mut(&mut self, name: &str) -> &mut Context {
+        self.contexts.entry(name.to_owned()).or_default()
+    }
+
+    /// Makes a context active until it is popped
+    ///
+    /// Returns `false` if no context has the name.
+    pub fn push_context(&mut self, name: &str) -> bool {
+        if !self.contexts.contains_key(name) {
+            return false;
+        }
+
+        self.stack.push(name.to_owned());
+        true
+    }
+
+    /// Deactivates the active context, reactivating the one beneath it
+    pub fn pop_context(&mut self) -> Option<String> {
+        self.stack.pop()
+    }
+
+    pub fn active_context(&self) -> Option<&str> {
+        self.stack.last().map(String::as_str)
+    }
+
+    /// Works out the commands issued and axis values of the active context
+    pub fn evaluate(&self, input: &InputState) -> (Commands, Axes) {
+        let mut commands = Commands(0.into());
+        let mut axes = Axes::new();
+
+        let context = match self.active_context().and_then(|name| self.contexts.get(name)) {
+            Some(context) => context,
+            None => return (commands, axes),
+        };
+
+        for (command, chord) in &context.actions {
+            if chord.is_held(input) {
+                commands.0.set(*command);
+            }
+        }
+
+        let mut values = [0.0; Axis::COUNT];
+        for (axis, negative, positive) in &context.axes {
+            if negative.is_held(input) {
+                values[*axis as usize] -= 1.0;
+            }
+
+            if positive.is_held(input) {
+                values[*axis as usize] += 1.0;
+            }
+        }
+
+        for axis in Axis::ALL {
+            axes.set(axis, values[axis as usize]);
+        }
+
+        (commands, axes)
+    }
+}
+
+impl Default for ActionMap {
+    /// The action map described by `DEFAULT_BINDINGS`
+    fn default() -> Self {
+        Self::parse(DEFAULT_BINDINGS).expect("default bindings are valid")
+    }
+}
+
+fn parse_chord(text: &str) -> Result<Chord, String> {
+    let keys = text.split('+')
+        .map(|name| Keys::from_name(name.trim()).ok_or_else(|| format!("unknown key `{}`", name.trim())))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Chord::new(keys))
+}
+
+/// Reasons an action map file cannot be loaded
+#[derive(Debug)]
+pub enum ActionMapError {
+    Io(io::Error),
+
+    /// A line of the file is malformed
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ActionMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActionMapError::Io(error) => write!(f, "could not read action map: {}", error),
+            ActionMapError::Parse { line, message } => write!(f, "action map line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for ActionMapError {}
+
+impl From<io::Error> for ActionMapError {
+    fn from(error: io::Error) -> Self {
+        ActionMapError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(keys: &[Keys]) -> InputState {
+        let mut input = InputState::new();
+        for key in keys {
+            input.press(*key);
+        }
+
+        input
+    }
+
+    #[test]
+    fn default_bindings_parse() {
+        let map = ActionMap::default();
+        assert_eq!(map.active_context(), Some("gameplay"));
+
+        let (commands, axes) = map.evaluate(&input(&[Keys::Escape, Keys::D, Keys::W, Keys::Up]));
+        assert!(commands.0.is_set(Command::Quit));
+        assert_eq!(axes.get(Axis::MoveX), 1.0);
+        assert_eq!(axes.get(Axis::MoveY), 1.0);
+    }
+
+    #[test]
+    fn chords_need_every_key_held() {
+        let map = ActionMap::parse("[gameplay]\naction quit = LControl+Q # both keys\n").unwrap();
+
+        assert!(!map.evaluate(&input(&[Keys::Q])).0.0.is_set(Command::Quit));
+        assert!(map.evaluate(&input(&[Keys::LControl, Keys::Q])).0.0.is_set(Command::Quit));
+    }
+
+    #[test]
+    fn opposing_axis_keys_cancel_out() {
+        let map = ActionMap::parse("[gameplay]\naxis move_x = A / D\n").unwrap();
+
+        assert_eq!(map.evaluate(&input(&[Keys::A])).1.get(Axis::MoveX), -1.0);
+        assert_eq!(map.evaluate(&input(&[Keys::A, Keys::D])).1.get(Axis::MoveX), 0.0);
+    }
+
+    #[test]
+    fn every_configured_axis_is_evaluated() {
+        let map = ActionMap::parse("[gameplay]\naxis move_y = S / W\n").unwrap();
+        let (_, axes) = map.evaluate(&input(&[Keys::S, Keys::D]));
+
+        assert_eq!(axes.get(Axis::MoveY), -1.0);
+        assert_eq!(axes.get(Axis::MoveX), 0.0);
+        assert!(Axis::ALL.iter().enumerate().all(|(index, axis)| *axis as usize == index));
+    }
+
+    #[test]
+    fn only_the_top_context_is_active() {
+        let mut map = ActionMap::parse(DEFAULT_BINDINGS).unwrap();
+        let escape = input(&[Keys::Escape]);
+
+        assert!(map.push_context("menu"));
+        assert!(!map.push_context("inventory"));
+        let (commands, _) = map.evaluate(&escape);
+        assert!(commands.0.is_set(Command::Cancel));
+        assert!(!commands.0.is_set(Command::Quit));
+
+        assert_eq!(map.pop_context(), Some("menu".to_owned()));
+        assert!(map.evaluate(&escape).0.0.is_set(Command::Quit));
+    }
+
+    #[test]
+    fn malformed_lines_report_their_number() {
+        match ActionMap::parse("[gameplay]\naction quit = Escape\naction jump = Space\n") {
+            Err(ActionMapError::Parse { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("expected a parse error"),
+        }
+
+        match ActionMap::parse("action quit = Escape") {
+            Err(ActionMapError::Parse { line, .. }) => assert_eq!(line, 1),
+            _ => panic!("expected a parse error"),
+        }
+
+        match ActionMap::parse("[gameplay]\naction quit = Escape\n[   ]\n") {
+            Err(ActionMapError::Parse { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("expected a parse error"),
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use app::{App, DefaultPlugin, Plugin};
+use component::Component;
+use event::Event;
+use replay::StateHash;
+use resource::Resource;
+use system::{Constraints, Executor, ScheduleBuilder, ScheduleError, System};
+use time::Time;
+use world::World;
+
+use std::any::TypeId;
+use std::thread;
+
+type Setup = Box<dyn FnOnce(&mut World)>;
+
+/// Collects the components, resources and systems of an `App` before it is built
+///
+/// Unless built `without_plugins`, the `DefaultPlugin` is added up front.
+pub struct AppBuilder {
+    world: World,
+    schedule: ScheduleBuilder,
+    startup: ScheduleBuilder,
+    setup: Vec<Setup>,
+    plugins: Vec<TypeId>,
+    executor: Executor,
+}
+
+impl AppBuilder {
+    pub fn new() -> Self {
+        Self::with_world(World::new())
+    }
+
+    /// Starts from an existing `World` rather than an empty one
+    pub fn with_world(world: World) -> Self {
+        let mut builder = Self::without_plugins(world);
+        builder.add_plugin(DefaultPlugin);
+        builder
+    }
+
+    /// Starts from an existing `World` without the `DefaultPlugin`, so that it can be replaced
+    pub fn without_plugins(world: World) -> Self {
+        let threads = thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1);
+        Self {
+            world,
+            schedule: ScheduleBuilder::new(),
+            startup: ScheduleBuilder::new(),
+            setup: Vec::new(),
+            plugins: Vec::new(),
+            executor: Executor::Parallel { threads },
+        }
+    }
+
+    /// Lets a plugin add its components, resources and systems
+    ///
+    /// Adding a plugin more than once has no effect.
+    pub fn add_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        if !self.plugins.contains(&TypeId::of::<P>()) {
+            self.plugins.push(TypeId::of::<P>());
+            plugin.build(self);
+        }
+
+        self
+    }
+
+    /// Registers a user component type with the world
+    pub fn register<T: Component>(&mut self) -> &mut Self {
+        self.world.register::<T>();
+        self
+    }
+
+    /// Includes components of type `T` in the world's state hash, registering `T` if needed
+    pub fn track_state<T: Component + StateHash>(&mut self) -> &mut Self {
+        self.world.track_state::<T>();
+        self
+    }
+
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> &mut Self {
+        self.world.insert_resource(resource);
+        self
+    }
+
+    /// Inserts the default value of a resource, unless the world already has one
+    pub fn init_resource<R: Resource + Default>(&mut self) -> &mut Self {
+        if !self.world.has_resource::<R>() {
+            self.world.insert_resource(R::default());
+        }
+
+        self
+    }
+
+    /// Adds a queue for events of type `T`, updated at the start of every tick
+    pub fn add_event<T: Event>(&mut self) -> &mut Self {
+        self.world.add_event::<T>();
+        self
+    }
+
+    /// Adds a user system to the schedule, returning its ordering constraints
+    pub fn add_system<S: System + 'static>(&mut self, name: &str, system: S) -> Constraints<'_> {
+        self.schedule.add(name, system)
+    }
+
+    /// Adds a system that runs once, after the setup closures, when the app is built
+    pub fn add_startup_system<S: System + 'static>(&mut self, name: &str, system: S) -> Constraints<'_> {
+        self.startup.add(name, system)
+    }
+
+    /// Runs a closure on the world once the app is built, such as to create the first entities
+    ///
+    /// Closures run in the order they were added.
+    pub fn setup<F: FnOnce(&mut World) + 'static>(&mut self, setup: F) -> &mut Self {
+        self.setup.push(Box::new(setup));
+        self
+    }
+
+    pub fn set_executor(&mut self, executor: Executor) -> &mut Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Schedules the systems, then runs the setup closures and startup systems
+    pub fn build(self) -> Result<App, ScheduleError> {
+        let mut schedule = self.schedule.build()?;
+        schedule.set_executor(self.executor);
+        let mut startup = self.startup.build()?;
+
+        let mut world = self.world;
+        for setup in self.setup {
+            setup(&mut world);
+        }
+
+        let time = if world.has_resource::<Time>() { *world.resource::<Time>() } else { Time::new() };
+        startup.run(&mut world, &time);
+
+        Ok(App::with_schedule(world, schedule))
+    }
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use component::Position;
+    use query::Join;
+    use storage::{Storage, Tick};
+    use storage::sequence::SequenceStorage;
+    use system::Access;
+    use time::Time;
+
+    struct Score(u32);
+
+    impl Component for Score {
+        type Storage = SequenceStorage<Self>;
+    }
+
+    struct ScoreSystem;
+
+    impl System for ScoreSystem {
+        fn access(&self) -> Access {
+            Access::new().write::<Score>()
+        }
+
+        fn run(&mut self, world: &World, _: &Time, _: Tick) {
+            for (_, (score,)) in (&mut world.write::<Score>(),).join() {
+                score.0 += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn user_components_systems_and_setup_are_applied() {
+        let mut builder = AppBuilder::new();
+        builder.register::<Score>().setup(|world| {
+            world.create_entity().with_component(Score(0)).with_component(Position(0.0, 0.0)).build();
+        });
+        builder.add_system("score", ScoreSystem).after("movement");
+
+        let mut app = builder.build().unwrap();
+        app.run_ticks(&mut ::app::source::NoInput, 2);
+
+        let scores = app.world().read::<Score>();
+        assert_eq!(scores.entities().iter().map(|entity| scores.get(*entity).unwrap().0).collect::<Vec<_>>(), vec![2]);
+    }
+
+    struct ScorePlugin;
+
+    impl Plugin for ScorePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.register::<Score>().setup(|world| {
+                world.create_entity().with_component(Score(10)).build();
+            });
+            app.add_startup_system("score", ScoreSystem);
+        }
+    }
+
+    #[test]
+    fn plugins_replace_the_default_systems() {
+        let mut builder = AppBuilder::without_plugins(World::new());
+        builder.add_plugin(ScorePlugin).add_plugin(ScorePlugin);
+        builder.add_system("score", ScoreSystem);
+
+        let mut app = builder.build().unwrap();
+        assert_eq!(app.schedule.order(), vec!["score"]);
+        assert!(!app.world().has_resource::<::input::InputState>() && !app.world().has_resource::<::time::Time>());
+
+        assert_eq!(app.run_ticks(&mut ::app::source::NoInput, 2), 2);
+        let scores = app.world().read::<Score>();
+        assert_eq!(scores.entities().iter().map(|entity| scores.get(*entity).unwrap().0).collect::<Vec<_>>(), vec![13]);
+    }
+
+    #[test]
+    fn unschedulable_systems_are_reported() {
+        let mut builder = AppBuilder::new();
+        builder.add_system("score", ScoreSystem).after("missing");
+
+        assert!(builder.build().is_err());
+    }
+}
@@ -1,88 +1,265 @@
-use self::run::{ExecutionFlow, ExecutionLoop};
-use command::Command;
-use component::Component::{Commands, KeysPressed};
-use input::Keys;
-use storage::{Storage, StorageMut};
-use system::System;
-use system::command::CommandSystem;
-use system::movement::MovementSystem;
+pub use self::builder::AppBuilder;
+pub use self::plugin::{DefaultPlugin, Plugin};
+
+use self::run::{ExecutionFlow, ExecutionLoop, FixedFrame, FixedTimestep};
+use self::source::InputSource;
+use camera::Camera;
+use command::QuitRequested;
+use event::{EventReader, Events};
+use input::{InputEvent, InputState};
+use replay::{Divergence, Recording};
+use system::Schedule;
+use time::Time;
 use world::World;
 
-use winit::{Event, EventsLoop, KeyboardInput, VirtualKeyCode, Window, WindowEvent};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use winit::{Event, EventsLoop, Window, WindowEvent};
 
+mod builder;
+mod plugin;
 pub mod run;
+pub mod source;
+
+const UPDATE_RATE: u8 = 60;
+const MAX_STEPS: u32 = 5;
 
 /// Container for systems that update a world in the execution loop
+///
+/// No window is opened until `run` or `run_recorded` is called, so `run_headless`, `run_ticks`
+/// and `run_replay` work without a display.
+///
+/// The app keeps its own clock, copied into the `Time` resource every tick if the world has
+/// one. Input, the camera and quitting are likewise skipped for worlds without their resources.
 pub struct App {
     world: World,
-    systems: Systems,
-    events: EventsLoop,
+    schedule: Schedule,
+    step: Duration,
+    tick_count: u64,
+    time: Time,
+    quit: EventReader<QuitRequested>,
 }
 
 impl App {
-    /// Passes ownership of a `World` to this `App`
+    /// Passes ownership of a `World` to an `App` running only the `DefaultPlugin`
     pub fn new(world: World) -> Self {
+        AppBuilder::with_world(world).build().expect("the default plugin should be schedulable")
+    }
+
+    /// Starts building an `App` with user components, systems and setup
+    pub fn builder() -> AppBuilder {
+        AppBuilder::new()
+    }
+
+    fn with_schedule(world: World, schedule: Schedule) -> Self {
+        let time = if world.has_resource::<Time>() { *world.resource::<Time>() } else { Time::new() };
         Self {
             world,
-            systems: Systems::new(),
-            events: EventsLoop::new(),
+            schedule,
+            step: FixedTimestep::from_rate(u32::from(UPDATE_RATE), MAX_STEPS).step(),
+            tick_count: 0,
+            time,
+            quit: EventReader::new(),
         }
     }
 
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// The number of ticks run so far
+    pub fn ticks(&self) -> u64 {
+        self.tick_count
+    }
+
     /// Runs the execution loop on its `World`
-    pub fn run(mut self) {
-        let _window = Window::new(&self.events).unwrap();
-
-        ExecutionLoop::new(60).run(|delta| {
-            let id = self.world.player_id();
-
-            // TODO: This is wasteful becuase the events are iterated through twice!
-            let mut events = Vec::new();
-            self.events.poll_events(|event| events.push(event));
-            for event in &events {
-                if let Some(player_id) = id {
-                    if let Some(player_keys) = (&mut self.world.keys).get_mut(player_id) {
-                        if let KeysPressed(keys) = player_keys {
-                            if let Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } = event {
-                                if let KeyboardInput { virtual_keycode: Some(key), .. } = input {
-                                    if *key == VirtualKeyCode::Escape {
-                                        keys.set(Keys::Escape)
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    pub fn run(self) {
+        self.run_window(|_, _| ());
+    }
+
+    /// Runs the execution loop like `run`, then saves every tick's input to `path`
+    pub fn run_recorded<P: AsRef<Path>>(self, path: P) -> io::Result<()> {
+        let mut recording = Recording::new(self.step);
+        self.run_window(|events, world| recording.push(events, world.state_hash()));
+
+        recording.save(path)
+    }
+
+    /// Feeds a recording's input back to the `World` without opening a window
+    ///
+    /// Ticks run as fast as possible. Returns the first tick whose state hash differs from the
+    /// recorded one.
+    pub fn run_replay(mut self, recording: &Recording) -> Result<(), Divergence> {
+        for (index, tick) in recording.ticks().iter().enumerate() {
+            let flow = self.tick(recording.step(), &tick.events);
+
+            let actual = self.world.state_hash();
+            if actual != tick.hash {
+                return Err(Divergence { tick: index, expected: tick.hash, actual });
+            }
+
+            if let ExecutionFlow::Quit = flow {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the execution loop without a window, taking input from `source` until a quit command
+    pub fn run_headless<S: InputSource>(mut self, mut source: S) {
+        let mut timestep = FixedTimestep::new(self.step, MAX_STEPS);
+        ExecutionLoop::new(UPDATE_RATE).run_fixed(&mut timestep, |frame| match frame {
+            FixedFrame::Step(delta) => {
+                let events = source.events(self.tick_count);
+                self.tick(delta, &events)
+            },
+            FixedFrame::Render(_) => ExecutionFlow::Continue,
+        })
+    }
+
+    /// Runs up to `ticks` ticks as fast as possible without a window, taking input from `source`
+    ///
+    /// Stops early on a quit command and returns the number of ticks run.
+    pub fn run_ticks<S: InputSource>(&mut self, source: &mut S, ticks: u64) -> u64 {
+        for count in 1..=ticks {
+            let events = source.events(self.tick_count);
+            if let ExecutionFlow::Quit = self.tick(self.step, &events) {
+                return count;
             }
+        }
+
+        ticks
+    }
 
-            self.systems.command.run(&mut self.world.commands, &self.world.keys, &delta);
-            self.systems.movement.run(&mut self.world.positions, &self.world.velocities, &delta);
+    /// Runs the execution loop, handing each tick's input and resulting world to `on_tick`
+    fn run_window<F: FnMut(Vec<InputEvent>, &World)>(mut self, mut on_tick: F) {
+        let mut events_loop = EventsLoop::new();
+        let window = Window::new(&events_loop).unwrap();
 
-            if let Some(player_id) = self.world.player_id() {
-                if let Some(player_commands) = (&self.world.commands).get(player_id) {
-                    if let Commands(commands) = player_commands {
-                        if commands.is_set(Command::Quit) {
-                            return ExecutionFlow::Quit;
-                        }
-                    }
+        // The first tick resizes the camera like any later resize, so recordings capture it
+        let mut initial_size = window.get_inner_size().map(|size| InputEvent::Resized(size.width, size.height));
+
+        let mut timestep = FixedTimestep::from_rate(u32::from(UPDATE_RATE), MAX_STEPS);
+        ExecutionLoop::new(UPDATE_RATE).run_fixed(&mut timestep, |frame| {
+            let delta = match frame {
+                FixedFrame::Step(delta) => delta,
+                FixedFrame::Render(_) => return ExecutionFlow::Continue,
+            };
+
+            let mut events: Vec<_> = initial_size.take().into_iter().collect();
+            let mut close_requested = false;
+            events_loop.poll_events(|event| {
+                if let Event::WindowEvent { event, .. } = event {
+                    close_requested |= matches!(event, WindowEvent::CloseRequested);
+                    events.extend(InputEvent::from_window_event(&event));
                 }
+            });
+
+            if close_requested && self.world.has_resource::<Events<QuitRequested>>() {
+                self.world.event_writer::<QuitRequested>().send(QuitRequested);
             }
 
-            ExecutionFlow::Continue
+            let flow = self.tick(delta, &events);
+            on_tick(events, &self.world);
+
+            if close_requested { ExecutionFlow::Quit } else { flow }
         })
     }
-}
 
-struct Systems {
-    command: CommandSystem,
-    movement: MovementSystem,
-}
+    /// Advances the `World` by one fixed step given the input that arrived during it
+    fn tick(&mut self, delta: Duration, events: &[InputEvent]) -> ExecutionFlow {
+        self.tick_count += 1;
+        self.world.update_events();
 
-impl Systems {
-    fn new() -> Self {
-        Self {
-            command: CommandSystem,
-            movement: MovementSystem,
+        if self.world.has_resource::<InputState>() {
+            let mut input = self.world.resource_mut::<InputState>();
+            input.advance(delta);
+            for event in events {
+                input.apply(event);
+            }
+        }
+
+        if self.world.has_resource::<Camera>() {
+            for event in events {
+                if let InputEvent::Resized(width, height) = *event {
+                    self.world.resource_mut::<Camera>().resize(width, height);
+                }
+            }
+        }
+
+        self.time.advance(delta);
+        if self.world.has_resource::<Time>() {
+            *self.world.resource_mut::<Time>() = self.time;
+        }
+
+        self.schedule.run(&mut self.world, &self.time);
+
+        if self.world.has_resource::<Events<QuitRequested>>()
+            && self.quit.read(&self.world.resource::<Events<QuitRequested>>()).next().is_some()
+        {
+            return ExecutionFlow::Quit;
         }
+
+        ExecutionFlow::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::source::{NoInput, ScriptedInput};
+    use component::{Position, Velocity};
+    use input::Keys;
+    use storage::Storage;
+
+    fn app() -> (App, ::entity::Entity) {
+        let mut app = App::new(World::new());
+        let player = app.world_mut().create_entity()
+            .with_component(Position(0.0, 0.0))
+            .with_component(Velocity(0.0, 0.0))
+            .make_player()
+            .build();
+
+        (app, player)
+    }
+
+    #[test]
+    fn headless_ticks_step_the_world() {
+        let (mut app, player) = app();
+        let mut input = ScriptedInput::new().at(0, InputEvent::KeyPressed(Keys::D));
+
+        assert_eq!(app.run_ticks(&mut input, 3), 3);
+        assert_eq!(app.ticks(), 3);
+        assert_eq!(app.world().read::<Velocity>().get(player), Some(&Velocity(3.0, 0.0)));
+        assert!(app.world().read::<Position>().get(player).unwrap().0 > 0.0);
+    }
+
+    #[test]
+    fn headless_ticks_stop_on_quit() {
+        let (mut app, _) = app();
+        let mut input = ScriptedInput::new()
+            .at(2, InputEvent::KeyPressed(Keys::Escape))
+            .at(3, InputEvent::KeyReleased(Keys::Escape));
+
+        assert_eq!(app.run_ticks(&mut input, 10), 3);
+        assert_eq!(app.run_ticks(&mut input, 10), 10);
+        assert_eq!(app.run_ticks(&mut NoInput, 5), 5);
+    }
+
+    #[test]
+    fn recorded_resizes_reach_the_camera() {
+        let (mut app, _) = app();
+        let mut recording = Recording::new(app.step);
+        recording.push(vec![InputEvent::Resized(640.0, 480.0)], 0);
+
+        app.run_ticks(&mut recording, 1);
+        assert_eq!(app.world().resource::<Camera>().window_size(), (640.0, 480.0));
     }
 }
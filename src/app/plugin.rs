@@ -0,0 +1,48 @@
+use action::{ActionMap, Axes};
+use app::AppBuilder;
+use camera::Camera;
+use command::{Commands, QuitRequested};
+use component::{Position, Velocity};
+use hierarchy::{Children, GlobalTransform, LocalTransform, Parent};
+use input::InputState;
+use system::command::CommandSystem;
+use system::keys::KeysSystem;
+use system::movement::MovementSystem;
+use system::transform::TransformSystem;
+use time::Time;
+
+use std::any::Any;
+
+/// A feature packaged as the components, resources and systems it adds to an app
+pub trait Plugin: Any {
+    fn build(&self, app: &mut AppBuilder);
+}
+
+/// The built-in input, command, movement and transform behaviour
+///
+/// Registers the built-in components, inserts default input, camera and time resources unless
+/// the world already has them, and adds systems named "command", "keys", "movement" and
+/// "transform", in that order, so that other systems can be ordered around them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultPlugin;
+
+impl Plugin for DefaultPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.track_state::<Position>().track_state::<Velocity>();
+        app.register::<LocalTransform>().register::<GlobalTransform>();
+        app.register::<Parent>().register::<Children>();
+
+        app.init_resource::<ActionMap>()
+            .init_resource::<Axes>()
+            .init_resource::<Camera>()
+            .init_resource::<Commands>()
+            .init_resource::<InputState>()
+            .init_resource::<Time>();
+        app.add_event::<QuitRequested>();
+
+        app.add_system("command", CommandSystem);
+        app.add_system("keys", KeysSystem).after("command");
+        app.add_system("movement", MovementSystem).after("keys");
+        app.add_system("transform", TransformSystem).after("movement");
+    }
+}
@@ -2,6 +2,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 const MINIMUM_FPS: u8 = 24;
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
 
 /// Process a callback function at a desired frequency per second
 pub struct ExecutionLoop {
@@ -36,9 +37,102 @@ impl ExecutionLoop {
             }
         }
     }
+
+    /// Run a simulation at a constant step in an infinite loop, rendering once per frame
+    ///
+    /// Each frame, the time elapsed is banked in `timestep` and the callback is asked to advance
+    /// the simulation by one step for every whole step banked, then to render.
+    pub fn run_fixed<F>(&mut self, timestep: &mut FixedTimestep, mut callback: F)
+    where
+        F: FnMut(FixedFrame) -> ExecutionFlow,
+    {
+        loop {
+            let now = Instant::now();
+            if let FrameAction::Run(delta) = skip_or_run(now, self.prev, self.frame_interval) {
+                for _ in 0..timestep.advance(delta) {
+                    if let ExecutionFlow::Quit = callback(FixedFrame::Step(timestep.step())) {
+                        return;
+                    }
+                }
+
+                match callback(FixedFrame::Render(timestep.alpha())) {
+                    ExecutionFlow::Continue => self.prev = now,
+                    ExecutionFlow::Quit => break,
+                }
+            }
+        }
+    }
+}
+
+/// Accumulates frame time and splits it into simulation steps of constant length
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    /// Initializes the timestep
+    ///
+    /// At most `max_steps` steps run per frame. Time beyond that is dropped, so a slow frame
+    /// slows the simulation down rather than making every following frame slower still.
+    ///
+    /// Panics if `step` is zero.
+    pub fn new(step: Duration, max_steps: u32) -> Self {
+        assert!(step > Duration::new(0, 0), "a fixed timestep needs a step longer than zero");
+        Self {
+            step,
+            max_steps: max_steps.max(1),
+            accumulator: Duration::new(0, 0),
+        }
+    }
+
+    /// Initializes the timestep to run a number of steps per second
+    ///
+    /// Rates above one step per nanosecond are clamped to it.
+    pub fn from_rate(steps_per_second: u32, max_steps: u32) -> Self {
+        Self::new(Duration::from_secs(1) / steps_per_second.clamp(1, NANOS_PER_SECOND), max_steps)
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Banks the time of a frame, returning how many steps to simulate
+    pub fn advance(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        if steps == self.max_steps && self.accumulator >= self.step {
+            self.accumulator = Duration::new(0, 0);
+        }
+
+        steps
+    }
+
+    /// How far between the last and next simulation step the current frame is, from 0 to 1
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.step.as_secs_f64()
+    }
+}
+
+/// A phase of a fixed-timestep frame passed to the callback function
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FixedFrame {
+    /// The simulation should advance by exactly this step
+    Step(Duration),
+
+    /// The world should be drawn, blending the last two simulation states by this fraction
+    Render(f64),
 }
 
 /// Signal returned by the callback function
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ExecutionFlow {
     /// The program should continue to the next loop iteration
     Continue,
@@ -108,4 +202,55 @@ mod tests {
         assert_eq!(loop_iterations, 2);
         assert!(execution_loop.prev > prev_on_init);
     }
+
+    #[test]
+    fn timestep_runs_one_step_per_whole_step_banked() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10), 5);
+
+        assert_eq!(timestep.advance(Duration::from_millis(25)), 2);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(Duration::from_millis(5)), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn timestep_caps_catch_up_steps() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10), 3);
+
+        assert_eq!(timestep.advance(Duration::from_millis(1_000)), 3);
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(timestep.advance(Duration::from_millis(10)), 1);
+    }
+
+    #[test]
+    fn timestep_rates_never_round_down_to_zero() {
+        assert_eq!(FixedTimestep::from_rate(u32::MAX, 1).step(), Duration::new(0, 1));
+        assert_eq!(FixedTimestep::from_rate(0, 1).step(), Duration::from_secs(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "longer than zero")]
+    fn zero_timesteps_panic() {
+        FixedTimestep::new(Duration::new(0, 0), 1);
+    }
+
+    #[test]
+    fn fixed_loop_steps_before_rendering() {
+        let mut execution_loop = ExecutionLoop::new(200);
+        let mut timestep = FixedTimestep::new(Duration::from_millis(1), 100);
+        let mut frames = Vec::new();
+
+        execution_loop.run_fixed(&mut timestep, |frame| {
+            frames.push(frame);
+            match frame {
+                FixedFrame::Step(_) => ExecutionFlow::Continue,
+                FixedFrame::Render(_) => ExecutionFlow::Quit,
+            }
+        });
+
+        let (last, steps) = frames.split_last().unwrap();
+        assert!(!steps.is_empty());
+        assert!(steps.iter().all(|frame| *frame == FixedFrame::Step(Duration::from_millis(1))));
+        assert!(match last { FixedFrame::Render(alpha) => *alpha >= 0.0 && *alpha < 1.0, _ => false });
+    }
 }
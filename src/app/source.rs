@@ -0,0 +1,67 @@
+use input::InputEvent;
+use replay::Recording;
+
+use std::collections::HashMap;
+
+/// Supplies the input for each tick when there is no window to poll
+pub trait InputSource {
+    /// The events that arrived during the given tick, counting from zero
+    fn events(&mut self, tick: u64) -> Vec<InputEvent>;
+}
+
+/// An input source that never produces any events
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoInput;
+
+impl InputSource for NoInput {
+    fn events(&mut self, _: u64) -> Vec<InputEvent> {
+        Vec::new()
+    }
+}
+
+/// Input events scheduled ahead of time for particular ticks
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedInput(HashMap<u64, Vec<InputEvent>>);
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event to those delivered at the given tick
+    pub fn at(mut self, tick: u64, event: InputEvent) -> Self {
+        self.0.entry(tick).or_default().push(event);
+        self
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn events(&mut self, tick: u64) -> Vec<InputEvent> {
+        self.0.remove(&tick).unwrap_or_default()
+    }
+}
+
+impl InputSource for Recording {
+    fn events(&mut self, tick: u64) -> Vec<InputEvent> {
+        self.ticks().get(tick as usize).map(|tick| tick.events.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::Keys;
+
+    #[test]
+    fn scripted_events_arrive_once_at_their_tick() {
+        let mut input = ScriptedInput::new()
+            .at(1, InputEvent::KeyPressed(Keys::W))
+            .at(1, InputEvent::KeyPressed(Keys::D))
+            .at(3, InputEvent::KeyReleased(Keys::W));
+
+        assert_eq!(input.events(0), Vec::new());
+        assert_eq!(input.events(1), vec![InputEvent::KeyPressed(Keys::W), InputEvent::KeyPressed(Keys::D)]);
+        assert_eq!(input.events(1), Vec::new());
+        assert_eq!(input.events(3), vec![InputEvent::KeyReleased(Keys::W)]);
+    }
+}
@@ -0,0 +1,67 @@
+/// Maps between window coordinates and world coordinates
+///
+/// Window coordinates are logical pixels from the top-left corner of the window, with y
+/// pointing down. World coordinates are centred on `center` with y pointing up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub center: (f64, f64),
+
+    /// Logical pixels per world unit
+    pub zoom: f64,
+    window_size: (f64, f64),
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self { center: (0.0, 0.0), zoom: 1.0, window_size: (0.0, 0.0) }
+    }
+
+    pub fn window_size(&self) -> (f64, f64) {
+        self.window_size
+    }
+
+    /// Records the window's size in logical pixels after it is created or resized
+    pub fn resize(&mut self, width: f64, height: f64) {
+        self.window_size = (width, height);
+    }
+
+    pub fn window_to_world(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (width, height) = self.window_size;
+        (self.center.0 + (x - width / 2.0) / self.zoom, self.center.1 - (y - height / 2.0) / self.zoom)
+    }
+
+    pub fn world_to_window(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (width, height) = self.window_size;
+        ((x - self.center.0) * self.zoom + width / 2.0, (self.center.1 - y) * self.zoom + height / 2.0)
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_centre_is_camera_centre() {
+        let mut camera = Camera::new();
+        camera.resize(800.0, 600.0);
+        camera.center = (10.0, -5.0);
+
+        assert_eq!(camera.window_to_world((400.0, 300.0)), (10.0, -5.0));
+    }
+
+    #[test]
+    fn window_and_world_coordinates_round_trip() {
+        let mut camera = Camera::new();
+        camera.resize(800.0, 600.0);
+        camera.zoom = 2.0;
+
+        assert_eq!(camera.window_to_world((600.0, 100.0)), (100.0, 100.0));
+        assert_eq!(camera.world_to_window((100.0, 100.0)), (600.0, 100.0));
+    }
+}
@@ -1,12 +1,37 @@
-use util::BitVectorStorage;
+use util::BitVector;
 
 /// Player-issued commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
     Quit,
+    Pause,
+    Confirm,
+    Cancel,
 }
 
-impl Into<BitVectorStorage> for Command {
-    fn into(self) -> BitVectorStorage {
-        self as BitVectorStorage
+impl Command {
+    /// Looks a command up by its lowercase name, as used in action map files
+    pub fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "quit" => Some(Command::Quit),
+            "pause" => Some(Command::Pause),
+            "confirm" => Some(Command::Confirm),
+            "cancel" => Some(Command::Cancel),
+            _ => None,
+        }
     }
 }
+
+impl From<Command> for usize {
+    fn from(value: Command) -> Self {
+        value as usize
+    }
+}
+
+/// The current commands being issued
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Commands(pub BitVector);
+
+/// Sent when the app should close, such as when the quit command is issued
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuitRequested;
@@ -0,0 +1,166 @@
+use component::Component;
+use entity::Entity;
+use world::{ComponentInsert, World};
+
+type Operation = Box<dyn FnOnce(&mut World) + Send>;
+
+/// Structural changes to a world, queued while it is borrowed and applied later
+///
+/// A system keeps its own buffer and hands it over in `System::apply`, which the schedule calls
+/// at the end of the system's stage. Buffers apply in the order the systems are scheduled, and
+/// each buffer applies its changes in the order they were queued.
+#[derive(Default)]
+pub struct CommandBuffer {
+    operations: Vec<Operation>,
+}
+
+impl CommandBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues an entity to be created with the components given to the returned builder
+    ///
+    /// The entity is reserved from `world` right away, so later commands can refer to it.
+    pub fn spawn(&mut self, world: &World) -> SpawnBuilder<'_> {
+        SpawnBuilder { buffer: self, entity: world.reserve_entity(), components: Vec::new(), is_player: false }
+    }
+
+    /// Queues an entity and all of its components to be destroyed
+    pub fn despawn(&mut self, entity: Entity) {
+        self.operations.push(Box::new(move |world: &mut World| {
+            world.destroy_entity(entity);
+        }));
+    }
+
+    /// Queues a component to be attached to an entity, replacing any of the same type
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) {
+        self.operations.push(Box::new(move |world: &mut World| world.insert_component(entity, component)));
+    }
+
+    /// Queues a component to be detached from an entity
+    pub fn remove<T: Component>(&mut self, entity: Entity) {
+        self.operations.push(Box::new(move |world: &mut World| {
+            world.remove_component::<T>(entity);
+        }));
+    }
+
+    /// Queues `child` to be attached to `parent`, as by `World::set_parent`
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.operations.push(Box::new(move |world: &mut World| {
+            world.set_parent(child, parent);
+        }));
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Applies every queued change to the world, leaving the buffer empty
+    pub fn apply(&mut self, world: &mut World) {
+        for operation in self.operations.drain(..) {
+            operation(world);
+        }
+    }
+}
+
+/// Collects the components of an entity queued in a `CommandBuffer`
+pub struct SpawnBuilder<'a> {
+    buffer: &'a mut CommandBuffer,
+    entity: Entity,
+    components: Vec<ComponentInsert>,
+    is_player: bool,
+}
+
+impl<'a> SpawnBuilder<'a> {
+    /// Adds a component of any registered type to the entity
+    pub fn with_component<T: Component>(mut self, component: T) -> Self {
+        self.components.push(Box::new(move |world: &mut World, entity| world.insert_component(entity, component)));
+        self
+    }
+
+    pub fn make_player(mut self) -> Self {
+        self.is_player = true;
+        self
+    }
+
+    /// Queues the entity to be created, returning the entity it will be
+    pub fn build(self) -> Entity {
+        let SpawnBuilder { buffer, entity, components, is_player } = self;
+        buffer.operations.push(Box::new(move |world: &mut World| world.spawn_reserved(entity, components, is_player)));
+        entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use component::{Position, Velocity};
+    use storage::Storage;
+    use util::BitVector;
+
+    #[test]
+    fn changes_wait_until_applied() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        let mut buffer = CommandBuffer::new();
+
+        let player = buffer.spawn(&world).with_component(Position(1.0, 2.0)).make_player().build();
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(world.player_id(), None);
+        assert!(!world.is_alive(player));
+
+        buffer.apply(&mut world);
+        assert!(buffer.is_empty());
+
+        assert_eq!(world.player_id(), Some(player));
+        assert_eq!(world.read::<Position>().get(player), Some(&Position(1.0, 2.0)));
+    }
+
+    #[test]
+    fn spawned_entities_can_be_targeted_before_they_exist() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        let root = world.create_entity().build();
+        let mut buffer = CommandBuffer::new();
+
+        let child = buffer.spawn(&world).with_component(Position(0.0, 0.0)).build();
+        buffer.insert(child, Velocity(1.0, 0.0));
+        buffer.set_parent(child, root);
+        let unused = world.reserve_entity();
+        buffer.apply(&mut world);
+
+        assert_eq!(world.read::<Velocity>().get(child), Some(&Velocity(1.0, 0.0)));
+        assert_eq!(world.children(root), [child]);
+        assert!(world.is_alive(unused) && unused != child);
+        assert_eq!(world.signature(unused), Some(&BitVector::new()));
+    }
+
+    #[test]
+    fn changes_apply_in_queued_order() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        let entity = world.create_entity().with_component(Position(0.0, 0.0)).build();
+        let mut buffer = CommandBuffer::new();
+
+        buffer.insert(entity, Velocity(1.0, 1.0));
+        buffer.remove::<Position>(entity);
+        buffer.insert(entity, Position(3.0, 3.0));
+        buffer.apply(&mut world);
+        assert_eq!(world.read::<Position>().get(entity), Some(&Position(3.0, 3.0)));
+        assert_eq!(world.read::<Velocity>().get(entity), Some(&Velocity(1.0, 1.0)));
+
+        buffer.despawn(entity);
+        buffer.insert(entity, Position(4.0, 4.0));
+        buffer.apply(&mut world);
+        assert!(!world.is_alive(entity));
+        assert_eq!(world.read::<Position>().get(entity), None);
+    }
+}
@@ -1,20 +1,44 @@
-use util::BitVector;
+use replay::{StateHash, StateHasher};
+use storage::{AnyStorage, DistinctStorage, StorageInsert};
+use storage::sequence::SequenceStorage;
+
+use std::any::Any;
+use std::hash::Hasher;
 
 /// Pieces of data that compose an entity
+///
+/// Implement this for a type and register it with `World::register` to attach it to entities.
+pub trait Component: Any + Send + Sync + Sized {
+    /// Where components of this type are kept
+    type Storage: StorageInsert<Component = Self> + DistinctStorage + AnyStorage + Default;
+}
+
+/// The world position of a component
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Component {
-    /// The absence of a component
-    Empty,
+pub struct Position(pub f64, pub f64);
+
+impl Component for Position {
+    type Storage = SequenceStorage<Self>;
+}
 
-    /// The world position of a component
-    Position(f64, f64),
+impl StateHash for Position {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u64(self.0.to_bits());
+        hasher.write_u64(self.1.to_bits());
+    }
+}
 
-    /// The world velocity of a component
-    Velocity(f64, f64),
+/// The world velocity of a component
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Velocity(pub f64, pub f64);
 
-    /// The state of the keys on the keyboard
-    KeysPressed(BitVector),
+impl Component for Velocity {
+    type Storage = SequenceStorage<Self>;
+}
 
-    /// The current commands being issued
-    Commands(BitVector),
+impl StateHash for Velocity {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u64(self.0.to_bits());
+        hasher.write_u64(self.1.to_bits());
+    }
 }
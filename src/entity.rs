@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+/// The number of times an entity slot has been reused
+pub type Generation = u32;
+
+/// A handle to an entity in a `World`
+///
+/// The generation tells a live entity apart from a stale handle whose slot has since been reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: usize,
+    generation: Generation,
+}
+
+impl Entity {
+    pub(crate) fn new(index: usize, generation: Generation) -> Self {
+        Self { index, generation }
+    }
+
+    /// The slot this entity occupies in the storages
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn generation(&self) -> Generation {
+        self.generation
+    }
+}
+
+/// Hands out entity handles, recycling the slots of freed entities
+///
+/// Handles can also be reserved through a shared reference, so that code without mutable access
+/// can refer to an entity before it exists. Reserved entities come alive at the next `flush`.
+#[derive(Default)]
+pub struct EntityAllocator {
+    generations: Vec<Generation>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    /// The free slots at or past this index are reserved, and a negative value counts the new
+    /// slots reserved past the end
+    free_cursor: AtomicIsize,
+}
+
+impl EntityAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allocate(&mut self) -> Entity {
+        self.flush();
+        let entity = match self.free.pop() {
+            Some(index) => {
+                self.alive[index] = true;
+                Entity::new(index, self.generations[index])
+            },
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity::new(self.generations.len() - 1, 0)
+            },
+        };
+
+        *self.free_cursor.get_mut() = self.free.len() as isize;
+        entity
+    }
+
+    /// Hands out an entity that stays dead until the next `flush`
+    pub fn reserve(&self) -> Entity {
+        let cursor = self.free_cursor.fetch_sub(1, Ordering::Relaxed);
+        if cursor > 0 {
+            let index = self.free[cursor as usize - 1];
+            Entity::new(index, self.generations[index])
+        } else {
+            Entity::new(self.generations.len() + (-cursor) as usize, 0)
+        }
+    }
+
+    /// Brings every reserved entity to life, returning them
+    pub fn flush(&mut self) -> Vec<Entity> {
+        let cursor = *self.free_cursor.get_mut();
+        let mut flushed = Vec::new();
+
+        let reused = if cursor >= 0 { cursor as usize } else { 0 };
+        for index in self.free.drain(reused..) {
+            self.alive[index] = true;
+            flushed.push(Entity::new(index, self.generations[index]));
+        }
+
+        for _ in cursor..0 {
+            self.generations.push(0);
+            self.alive.push(true);
+            flushed.push(Entity::new(self.generations.len() - 1, 0));
+        }
+
+        *self.free_cursor.get_mut() = self.free.len() as isize;
+        flushed
+    }
+
+    /// Frees the slot of a live entity so it can be reused with the next generation
+    ///
+    /// Returns `false` if the entity was already freed.
+    pub fn deallocate(&mut self, entity: Entity) -> bool {
+        self.flush();
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        self.alive[entity.index] = false;
+        self.generations[entity.index] += 1;
+        self.free.push(entity.index);
+        *self.free_cursor.get_mut() = self.free.len() as isize;
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.alive.get(entity.index).cloned().unwrap_or(false)
+            && self.generations[entity.index] == entity.generation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_sequential_indices() {
+        let mut allocator = EntityAllocator::new();
+        assert_eq!(allocator.allocate(), Entity::new(0, 0));
+        assert_eq!(allocator.allocate(), Entity::new(1, 0));
+    }
+
+    #[test]
+    fn freed_slots_are_reused_with_next_generation() {
+        let mut allocator = EntityAllocator::new();
+        let first = allocator.allocate();
+        allocator.allocate();
+
+        assert!(allocator.deallocate(first));
+        assert!(!allocator.is_alive(first));
+
+        let reused = allocator.allocate();
+        assert_eq!(reused, Entity::new(0, 1));
+        assert!(allocator.is_alive(reused));
+        assert!(!allocator.is_alive(first));
+    }
+
+    #[test]
+    fn stale_handles_cannot_be_deallocated() {
+        let mut allocator = EntityAllocator::new();
+        let entity = allocator.allocate();
+
+        assert!(allocator.deallocate(entity));
+        allocator.allocate();
+        assert!(!allocator.deallocate(entity));
+    }
+
+    #[test]
+    fn reserved_entities_come_alive_when_flushed() {
+        let mut allocator = EntityAllocator::new();
+        let freed = allocator.allocate();
+        allocator.allocate();
+        allocator.deallocate(freed);
+
+        let (reused, new) = (allocator.reserve(), allocator.reserve());
+        assert_eq!((reused, new), (Entity::new(0, 1), Entity::new(2, 0)));
+        assert!(!allocator.is_alive(reused) && !allocator.is_alive(new));
+
+        assert_eq!(allocator.flush(), [reused, new]);
+        assert!(allocator.is_alive(reused) && allocator.is_alive(new));
+        assert!(allocator.flush().is_empty());
+        assert_eq!(allocator.allocate(), Entity::new(3, 0));
+    }
+}
@@ -0,0 +1,136 @@
+use resource::ResMut;
+
+use std::marker::PhantomData;
+use std::mem;
+
+/// Messages that systems send to each other, such as collisions or quit requests
+pub trait Event: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Event for T {}
+
+/// A queue of events of one type, kept in a `World` by `World::add_event`
+///
+/// The queue is double-buffered. Events sent during a frame stay readable until the end of the
+/// next frame, so a reader that runs before the sender still sees them, but no later.
+pub struct Events<T: Event> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    previous_start: usize,
+    current_start: usize,
+}
+
+impl<T: Event> Events<T> {
+    pub fn new() -> Self {
+        Self { previous: Vec::new(), current: Vec::new(), previous_start: 0, current_start: 0 }
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+    }
+
+    /// Starts a new frame, dropping the events sent two frames ago
+    pub fn update(&mut self) {
+        self.previous = mem::take(&mut self.current);
+        self.previous_start = self.current_start;
+        self.current_start += self.previous.len();
+    }
+
+    /// The number of events that can still be read
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn end(&self) -> usize {
+        self.current_start + self.current.len()
+    }
+}
+
+impl<T: Event> Default for Events<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends events into a queue borrowed from a `World`
+pub struct EventWriter<'a, T: Event>(ResMut<'a, Events<T>>);
+
+impl<'a, T: Event> EventWriter<'a, T> {
+    pub(crate) fn new(events: ResMut<'a, Events<T>>) -> Self {
+        EventWriter(events)
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.0.send(event);
+    }
+}
+
+/// Reads each event from a queue exactly once
+///
+/// Every system that reads a type of event keeps its own reader, remembering how far it has read.
+pub struct EventReader<T: Event> {
+    next: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Event> EventReader<T> {
+    pub fn new() -> Self {
+        Self { next: 0, marker: PhantomData }
+    }
+
+    /// The events sent since this reader last read, oldest first
+    pub fn read<'a>(&mut self, events: &'a Events<T>) -> impl Iterator<Item = &'a T> {
+        let previous = self.next.max(events.previous_start) - events.previous_start;
+        let current = self.next.max(events.current_start) - events.current_start;
+        self.next = events.end();
+
+        events.previous.iter().skip(previous).chain(events.current.iter().skip(current))
+    }
+}
+
+impl<T: Event> Default for EventReader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readers_see_each_event_once() {
+        let mut events = Events::new();
+        let mut early = EventReader::new();
+        let mut late = EventReader::new();
+
+        events.send(1);
+        assert_eq!(early.read(&events).collect::<Vec<_>>(), vec![&1]);
+
+        events.update();
+        events.send(2);
+        assert_eq!(early.read(&events).collect::<Vec<_>>(), vec![&2]);
+        assert_eq!(late.read(&events).collect::<Vec<_>>(), vec![&1, &2]);
+        assert_eq!(late.read(&events).count(), 0);
+    }
+
+    #[test]
+    fn events_expire_after_two_updates() {
+        let mut events = Events::new();
+        let mut reader = EventReader::new();
+
+        events.send("collision");
+        events.update();
+        assert_eq!(events.len(), 1);
+
+        events.update();
+        assert!(events.is_empty());
+        assert_eq!(reader.read(&events).count(), 0);
+
+        events.send("damage");
+        assert_eq!(reader.read(&events).collect::<Vec<_>>(), vec![&"damage"]);
+    }
+}
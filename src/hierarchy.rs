@@ -0,0 +1,113 @@
+use component::Component;
+use entity::Entity;
+use storage::sequence::SequenceStorage;
+use storage::sparse::SparseSetStorage;
+
+/// A translation, rotation and scale in 2D, applied to points in that order reversed
+///
+/// Scale is uniform so that composing transforms never introduces shear.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: (f64, f64),
+
+    /// Counter-clockwise, in radians
+    pub rotation: f64,
+    pub scale: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform { translation: (0.0, 0.0), rotation: 0.0, scale: 1.0 };
+
+    pub fn from_translation(x: f64, y: f64) -> Self {
+        Transform { translation: (x, y), ..Self::IDENTITY }
+    }
+
+    /// Scales, rotates, then translates a point
+    pub fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (x * self.scale, y * self.scale);
+        (x * cos - y * sin + self.translation.0, x * sin + y * cos + self.translation.1)
+    }
+
+    /// The transform applying `child` first and then this one
+    pub fn then(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self.apply(child.translation),
+            rotation: self.rotation + child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// An entity's transform relative to its parent, or to the world if it has none
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LocalTransform(pub Transform);
+
+impl Component for LocalTransform {
+    type Storage = SequenceStorage<Self>;
+}
+
+/// An entity's transform relative to the world, computed from the local transforms of it and its
+/// ancestors by the "transform" system
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlobalTransform(pub Transform);
+
+impl Component for GlobalTransform {
+    type Storage = SequenceStorage<Self>;
+}
+
+/// The entity another is attached to, set with `World::set_parent`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Parent(pub(crate) Entity);
+
+impl Parent {
+    pub fn entity(&self) -> Entity {
+        self.0
+    }
+}
+
+impl Component for Parent {
+    type Storage = SparseSetStorage<Self>;
+}
+
+/// The entities attached to another, in the order they were attached
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Children(pub(crate) Vec<Entity>);
+
+impl Children {
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.0
+    }
+}
+
+impl Component for Children {
+    type Storage = SparseSetStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_close((x, y): (f64, f64), (expected_x, expected_y): (f64, f64)) {
+        assert!((x - expected_x).abs() < 1e-9 && (y - expected_y).abs() < 1e-9, "({}, {}) != ({}, {})", x, y, expected_x, expected_y);
+    }
+
+    #[test]
+    fn composed_transforms_apply_child_first() {
+        let parent = Transform { translation: (10.0, 0.0), rotation: FRAC_PI_2, scale: 2.0 };
+        let child = Transform::from_translation(1.0, 0.0);
+        let global = parent.then(&child);
+
+        assert_close(global.translation, (10.0, 2.0));
+        assert_close(global.apply((1.0, 0.0)), parent.apply(child.apply((1.0, 0.0))));
+        assert_eq!(Transform::IDENTITY.then(&child), child);
+    }
+}
@@ -1,16 +1,682 @@
-use util::BitVectorStorage;
+use camera::Camera;
+use util::BitVector;
+
+use std::time::Duration;
+
+use winit::{ElementState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+
+/// How many logical pixels of touchpad scrolling make up one line of wheel scrolling
+pub const PIXELS_PER_LINE: f64 = 20.0;
 
 /// Keys on a keyboard
+///
+/// Each key is a bit in a `BitVector`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Keys {
     Escape,
-    W,
-    S,
+
+    // Letters
     A,
+    B,
+    C,
     D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+
+    // Digits on the main row
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+
+    // Arrows
+    Left,
+    Up,
+    Right,
+    Down,
+
+    // Modifiers
+    LShift,
+    RShift,
+    LControl,
+    RControl,
+    LAlt,
+    RAlt,
+    LWin,
+    RWin,
+
+    // Function keys
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+
+    // Editing and navigation
+    Tab,
+    Space,
+    Return,
+    Back,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+
+    // Locks and system
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    Pause,
+    PrintScreen,
+
+    // Punctuation
+    Apostrophe,
+    Backslash,
+    Comma,
+    Equals,
+    Grave,
+    LBracket,
+    RBracket,
+    Minus,
+    Period,
+    Semicolon,
+    Slash,
+    Colon,
+    At,
+    Caret,
+    Underline,
+
+    // Numeric keypad
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+    NumpadEquals,
+    NumpadComma,
+}
+
+impl Keys {
+    /// The number of keys, and so the number of bits needed to hold their state
+    pub const COUNT: usize = Keys::NumpadComma as usize + 1;
+
+    /// Every key, in bit order
+    pub const ALL: [Keys; Keys::COUNT] = [
+        Keys::Escape, Keys::A, Keys::B, Keys::C, Keys::D, Keys::E, Keys::F, Keys::G, Keys::H, Keys::I, Keys::J,
+        Keys::K, Keys::L, Keys::M, Keys::N, Keys::O, Keys::P, Keys::Q, Keys::R, Keys::S, Keys::T, Keys::U,
+        Keys::V, Keys::W, Keys::X, Keys::Y, Keys::Z, Keys::Key0, Keys::Key1, Keys::Key2, Keys::Key3, Keys::Key4,
+        Keys::Key5, Keys::Key6, Keys::Key7, Keys::Key8, Keys::Key9, Keys::Left, Keys::Up, Keys::Right,
+        Keys::Down, Keys::LShift, Keys::RShift, Keys::LControl, Keys::RControl, Keys::LAlt, Keys::RAlt,
+        Keys::LWin, Keys::RWin, Keys::F1, Keys::F2, Keys::F3, Keys::F4, Keys::F5, Keys::F6, Keys::F7, Keys::F8,
+        Keys::F9, Keys::F10, Keys::F11, Keys::F12, Keys::F13, Keys::F14, Keys::F15, Keys::Tab, Keys::Space,
+        Keys::Return, Keys::Back, Keys::Insert, Keys::Delete, Keys::Home, Keys::End, Keys::PageUp,
+        Keys::PageDown, Keys::CapsLock, Keys::NumLock, Keys::ScrollLock, Keys::Pause, Keys::PrintScreen,
+        Keys::Apostrophe, Keys::Backslash, Keys::Comma, Keys::Equals, Keys::Grave, Keys::LBracket,
+        Keys::RBracket, Keys::Minus, Keys::Period, Keys::Semicolon, Keys::Slash, Keys::Colon, Keys::At,
+        Keys::Caret, Keys::Underline, Keys::Numpad0, Keys::Numpad1, Keys::Numpad2, Keys::Numpad3, Keys::Numpad4,
+        Keys::Numpad5, Keys::Numpad6, Keys::Numpad7, Keys::Numpad8, Keys::Numpad9, Keys::NumpadAdd,
+        Keys::NumpadSubtract, Keys::NumpadMultiply, Keys::NumpadDivide, Keys::NumpadDecimal, Keys::NumpadEnter,
+        Keys::NumpadEquals, Keys::NumpadComma,
+    ];
+
+    /// Looks a key up by its variant name, ignoring case
+    pub fn from_name(name: &str) -> Option<Keys> {
+        Keys::ALL.iter().find(|key| format!("{:?}", key).eq_ignore_ascii_case(name)).cloned()
+    }
+
+    /// Translates a key reported by the window, if it is one that can be tracked
+    pub fn from_virtual_keycode(code: VirtualKeyCode) -> Option<Keys> {
+        use self::Keys::*;
+
+        Some(match code {
+            VirtualKeyCode::Escape => Escape,
+            VirtualKeyCode::A => A,
+            VirtualKeyCode::B => B,
+            VirtualKeyCode::C => C,
+            VirtualKeyCode::D => D,
+            VirtualKeyCode::E => E,
+            VirtualKeyCode::F => F,
+            VirtualKeyCode::G => G,
+            VirtualKeyCode::H => H,
+            VirtualKeyCode::I => I,
+            VirtualKeyCode::J => J,
+            VirtualKeyCode::K => K,
+            VirtualKeyCode::L => L,
+            VirtualKeyCode::M => M,
+            VirtualKeyCode::N => N,
+            VirtualKeyCode::O => O,
+            VirtualKeyCode::P => P,
+            VirtualKeyCode::Q => Q,
+            VirtualKeyCode::R => R,
+            VirtualKeyCode::S => S,
+            VirtualKeyCode::T => T,
+            VirtualKeyCode::U => U,
+            VirtualKeyCode::V => V,
+            VirtualKeyCode::W => W,
+            VirtualKeyCode::X => X,
+            VirtualKeyCode::Y => Y,
+            VirtualKeyCode::Z => Z,
+            VirtualKeyCode::Key0 => Key0,
+            VirtualKeyCode::Key1 => Key1,
+            VirtualKeyCode::Key2 => Key2,
+            VirtualKeyCode::Key3 => Key3,
+            VirtualKeyCode::Key4 => Key4,
+            VirtualKeyCode::Key5 => Key5,
+            VirtualKeyCode::Key6 => Key6,
+            VirtualKeyCode::Key7 => Key7,
+            VirtualKeyCode::Key8 => Key8,
+            VirtualKeyCode::Key9 => Key9,
+            VirtualKeyCode::Left => Left,
+            VirtualKeyCode::Up => Up,
+            VirtualKeyCode::Right => Right,
+            VirtualKeyCode::Down => Down,
+            VirtualKeyCode::LShift => LShift,
+            VirtualKeyCode::RShift => RShift,
+            VirtualKeyCode::LControl => LControl,
+            VirtualKeyCode::RControl => RControl,
+            VirtualKeyCode::LAlt => LAlt,
+            VirtualKeyCode::RAlt => RAlt,
+            VirtualKeyCode::LWin => LWin,
+            VirtualKeyCode::RWin => RWin,
+            VirtualKeyCode::F1 => F1,
+            VirtualKeyCode::F2 => F2,
+            VirtualKeyCode::F3 => F3,
+            VirtualKeyCode::F4 => F4,
+            VirtualKeyCode::F5 => F5,
+            VirtualKeyCode::F6 => F6,
+            VirtualKeyCode::F7 => F7,
+            VirtualKeyCode::F8 => F8,
+            VirtualKeyCode::F9 => F9,
+            VirtualKeyCode::F10 => F10,
+            VirtualKeyCode::F11 => F11,
+            VirtualKeyCode::F12 => F12,
+            VirtualKeyCode::F13 => F13,
+            VirtualKeyCode::F14 => F14,
+            VirtualKeyCode::F15 => F15,
+            VirtualKeyCode::Tab => Tab,
+            VirtualKeyCode::Space => Space,
+            VirtualKeyCode::Return => Return,
+            VirtualKeyCode::Back => Back,
+            VirtualKeyCode::Insert => Insert,
+            VirtualKeyCode::Delete => Delete,
+            VirtualKeyCode::Home => Home,
+            VirtualKeyCode::End => End,
+            VirtualKeyCode::PageUp => PageUp,
+            VirtualKeyCode::PageDown => PageDown,
+            VirtualKeyCode::Capital => CapsLock,
+            VirtualKeyCode::Numlock => NumLock,
+            VirtualKeyCode::Scroll => ScrollLock,
+            VirtualKeyCode::Pause => Pause,
+            VirtualKeyCode::Snapshot => PrintScreen,
+            VirtualKeyCode::Apostrophe => Apostrophe,
+            VirtualKeyCode::Backslash => Backslash,
+            VirtualKeyCode::Comma => Comma,
+            VirtualKeyCode::Equals => Equals,
+            VirtualKeyCode::Grave => Grave,
+            VirtualKeyCode::LBracket => LBracket,
+            VirtualKeyCode::RBracket => RBracket,
+            VirtualKeyCode::Minus => Minus,
+            VirtualKeyCode::Period => Period,
+            VirtualKeyCode::Semicolon => Semicolon,
+            VirtualKeyCode::Slash => Slash,
+            VirtualKeyCode::Colon => Colon,
+            VirtualKeyCode::At => At,
+            VirtualKeyCode::Caret => Caret,
+            VirtualKeyCode::Underline => Underline,
+            VirtualKeyCode::Numpad0 => Numpad0,
+            VirtualKeyCode::Numpad1 => Numpad1,
+            VirtualKeyCode::Numpad2 => Numpad2,
+            VirtualKeyCode::Numpad3 => Numpad3,
+            VirtualKeyCode::Numpad4 => Numpad4,
+            VirtualKeyCode::Numpad5 => Numpad5,
+            VirtualKeyCode::Numpad6 => Numpad6,
+            VirtualKeyCode::Numpad7 => Numpad7,
+            VirtualKeyCode::Numpad8 => Numpad8,
+            VirtualKeyCode::Numpad9 => Numpad9,
+            VirtualKeyCode::Add => NumpadAdd,
+            VirtualKeyCode::Subtract => NumpadSubtract,
+            VirtualKeyCode::Multiply => NumpadMultiply,
+            VirtualKeyCode::Divide => NumpadDivide,
+            VirtualKeyCode::Decimal => NumpadDecimal,
+            VirtualKeyCode::NumpadEnter => NumpadEnter,
+            VirtualKeyCode::NumpadEquals => NumpadEquals,
+            VirtualKeyCode::NumpadComma => NumpadComma,
+            _ => return None,
+        })
+    }
+}
+
+impl From<Keys> for usize {
+    fn from(value: Keys) -> Self {
+        value as usize
+    }
+}
+
+/// Buttons on a mouse
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButtons {
+    Left,
+    Right,
+    Middle,
+}
+
+impl MouseButtons {
+    /// Every button, in bit order
+    pub const ALL: [MouseButtons; 3] = [MouseButtons::Left, MouseButtons::Right, MouseButtons::Middle];
+
+    /// Translates a winit mouse button, if it is one we track
+    pub fn from_mouse_button(button: MouseButton) -> Option<MouseButtons> {
+        match button {
+            MouseButton::Left => Some(MouseButtons::Left),
+            MouseButton::Right => Some(MouseButtons::Right),
+            MouseButton::Middle => Some(MouseButtons::Middle),
+            MouseButton::Other(_) => None,
+        }
+    }
+}
+
+impl From<MouseButtons> for usize {
+    fn from(value: MouseButtons) -> Self {
+        value as usize
+    }
+}
+
+/// A window event translated into the input it represents
+///
+/// Positions and sizes are in logical pixels, and scrolling is in lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    KeyPressed(Keys),
+    KeyReleased(Keys),
+    ButtonPressed(MouseButtons),
+    ButtonReleased(MouseButtons),
+    CursorMoved(f64, f64),
+    CursorLeft,
+    Scrolled(f64, f64),
+    Resized(f64, f64),
+}
+
+impl InputEvent {
+    /// Translates a window event, if it is one we track
+    pub fn from_window_event(event: &WindowEvent) -> Option<InputEvent> {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                input.virtual_keycode.and_then(Keys::from_virtual_keycode).map(|key| match input.state {
+                    ElementState::Pressed => InputEvent::KeyPressed(key),
+                    ElementState::Released => InputEvent::KeyReleased(key),
+                })
+            },
+            WindowEvent::MouseInput { state, button, .. } => {
+                MouseButtons::from_mouse_button(*button).map(|button| match state {
+                    ElementState::Pressed => InputEvent::ButtonPressed(button),
+                    ElementState::Released => InputEvent::ButtonReleased(button),
+                })
+            },
+            WindowEvent::CursorMoved { position, .. } => Some(InputEvent::CursorMoved(position.x, position.y)),
+            WindowEvent::CursorLeft { .. } => Some(InputEvent::CursorLeft),
+            WindowEvent::MouseWheel { delta, .. } => Some(match delta {
+                MouseScrollDelta::LineDelta(x, y) => InputEvent::Scrolled(f64::from(*x), f64::from(*y)),
+                MouseScrollDelta::PixelDelta(position) => {
+                    InputEvent::Scrolled(position.x / PIXELS_PER_LINE, position.y / PIXELS_PER_LINE)
+                },
+            }),
+            WindowEvent::Resized(size) => Some(InputEvent::Resized(size.width, size.height)),
+            _ => None,
+        }
+    }
+}
+
+/// The state of the keyboard and mouse this frame and the last
+///
+/// Window events update the current state as they arrive, and `advance` starts each frame so
+/// that presses and releases can be told apart from keys that are simply held.
+///
+/// A key or button released in the same frame it went down stays held until the next `advance`,
+/// so a tap shorter than a frame still reports a press that frame and a release the next.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputState {
+    current: BitVector,
+    previous: BitVector,
+    deferred: BitVector,
+    hold_durations: [Duration; Keys::COUNT],
+    buttons: BitVector,
+    previous_buttons: BitVector,
+    deferred_buttons: BitVector,
+    cursor: Option<(f64, f64)>,
+    scroll: (f64, f64),
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::from(BitVector::new())
+    }
+
+    /// Starts a new frame, following a frame that lasted `delta`
+    pub fn advance(&mut self, delta: Duration) {
+        for (index, duration) in self.hold_durations.iter_mut().enumerate() {
+            if self.current.is_set(index) {
+                *duration += delta;
+            } else {
+                *duration = Duration::new(0, 0);
+            }
+        }
+
+        self.previous = self.current.clone();
+        self.previous_buttons = self.buttons.clone();
+        self.current = self.current.difference(&self.deferred);
+        self.buttons = self.buttons.difference(&self.deferred_buttons);
+        self.deferred = BitVector::new();
+        self.deferred_buttons = BitVector::new();
+        self.scroll = (0.0, 0.0);
+    }
+
+    /// Updates the state with a translated input event
+    pub fn apply(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::KeyPressed(key) => self.press(key),
+            InputEvent::KeyReleased(key) => self.release(key),
+            InputEvent::ButtonPressed(button) => self.press_button(button),
+            InputEvent::ButtonReleased(button) => self.release_button(button),
+            InputEvent::CursorMoved(x, y) => self.move_cursor(x, y),
+            InputEvent::CursorLeft => self.cursor = None,
+            InputEvent::Scrolled(x, y) => self.scroll(x, y),
+            InputEvent::Resized(..) => (),
+        }
+    }
+
+    pub fn press(&mut self, key: Keys) {
+        self.current.set(key);
+        self.deferred.unset(key);
+    }
+
+    /// Lets a key up, or if it only went down this frame, lets it up at the next `advance`
+    pub fn release(&mut self, key: Keys) {
+        if self.just_pressed(key) {
+            self.deferred.set(key);
+        } else {
+            self.current.unset(key);
+        }
+    }
+
+    /// Whether the key is down this frame
+    pub fn held(&self, key: Keys) -> bool {
+        self.current.is_set(key)
+    }
+
+    /// Whether the key went down this frame
+    pub fn just_pressed(&self, key: Keys) -> bool {
+        self.current.is_set(key) && !self.previous.is_set(key)
+    }
+
+    /// Whether the key came up this frame
+    pub fn just_released(&self, key: Keys) -> bool {
+        !self.current.is_set(key) && self.previous.is_set(key)
+    }
+
+    /// How long the key has been down before this frame, or zero if it is up
+    pub fn hold_duration(&self, key: Keys) -> Duration {
+        if self.held(key) { self.hold_durations[key as usize] } else { Duration::new(0, 0) }
+    }
+
+    /// The keys down this frame
+    pub fn pressed(&self) -> &BitVector {
+        &self.current
+    }
+
+    pub fn press_button(&mut self, button: MouseButtons) {
+        self.buttons.set(button);
+        self.deferred_buttons.unset(button);
+    }
+
+    /// Lets a button up, or if it only went down this frame, lets it up at the next `advance`
+    pub fn release_button(&mut self, button: MouseButtons) {
+        if self.button_just_pressed(button) {
+            self.deferred_buttons.set(button);
+        } else {
+            self.buttons.unset(button);
+        }
+    }
+
+    /// Whether the mouse button is down this frame
+    pub fn button_held(&self, button: MouseButtons) -> bool {
+        self.buttons.is_set(button)
+    }
+
+    /// Whether the mouse button went down this frame
+    pub fn button_just_pressed(&self, button: MouseButtons) -> bool {
+        self.buttons.is_set(button) && !self.previous_buttons.is_set(button)
+    }
+
+    /// Whether the mouse button came up this frame
+    pub fn button_just_released(&self, button: MouseButtons) -> bool {
+        !self.buttons.is_set(button) && self.previous_buttons.is_set(button)
+    }
+
+    /// Moves the cursor to a position in window coordinates
+    pub fn move_cursor(&mut self, x: f64, y: f64) {
+        self.cursor = Some((x, y));
+    }
+
+    /// The cursor's position in window coordinates, or `None` if it is outside the window
+    pub fn cursor(&self) -> Option<(f64, f64)> {
+        self.cursor
+    }
+
+    /// The cursor's position in world coordinates, or `None` if it is outside the window
+    pub fn cursor_world(&self, camera: &Camera) -> Option<(f64, f64)> {
+        self.cursor.map(|position| camera.window_to_world(position))
+    }
+
+    /// Adds to this frame's scrolling, measured in lines
+    pub fn scroll(&mut self, x: f64, y: f64) {
+        self.scroll.0 += x;
+        self.scroll.1 += y;
+    }
+
+    /// The lines scrolled this frame, positive when scrolling right or away from the user
+    pub fn scroll_delta(&self) -> (f64, f64) {
+        self.scroll
+    }
 }
 
-impl Into<BitVectorStorage> for Keys {
-    fn into(self) -> BitVectorStorage {
-        self as BitVectorStorage
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<BitVector> for InputState {
+    /// Creates a state where the given keys were pressed this frame
+    fn from(pressed: BitVector) -> Self {
+        Self {
+            current: pressed,
+            previous: BitVector::new(),
+            deferred: BitVector::new(),
+            hold_durations: [Duration::new(0, 0); Keys::COUNT],
+            buttons: BitVector::new(),
+            previous_buttons: BitVector::new(),
+            deferred_buttons: BitVector::new(),
+            cursor: None,
+            scroll: (0.0, 0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_keycodes_are_translated() {
+        assert_eq!(Keys::from_virtual_keycode(VirtualKeyCode::Escape), Some(Keys::Escape));
+        assert_eq!(Keys::from_virtual_keycode(VirtualKeyCode::Key7), Some(Keys::Key7));
+        assert_eq!(Keys::from_virtual_keycode(VirtualKeyCode::Capital), Some(Keys::CapsLock));
+        assert_eq!(Keys::from_virtual_keycode(VirtualKeyCode::Add), Some(Keys::NumpadAdd));
+        assert_eq!(Keys::from_virtual_keycode(VirtualKeyCode::Mute), None);
+    }
+
+    #[test]
+    fn keys_are_listed_in_bit_order() {
+        assert!(Keys::ALL.iter().enumerate().all(|(index, key)| *key as usize == index));
+        assert_eq!(Keys::from_name("lshift"), Some(Keys::LShift));
+        assert_eq!(Keys::from_name("Escape"), Some(Keys::Escape));
+        assert_eq!(Keys::from_name("Hyper"), None);
+    }
+
+    #[test]
+    fn press_and_release_update_bits() {
+        let mut state = InputState::new();
+
+        state.apply(&InputEvent::KeyPressed(Keys::W));
+        state.apply(&InputEvent::KeyPressed(Keys::F12));
+        assert!(state.held(Keys::W));
+        assert!(state.held(Keys::F12));
+
+        state.advance(Duration::from_millis(16));
+        state.apply(&InputEvent::KeyReleased(Keys::W));
+        assert!(!state.held(Keys::W));
+        assert!(state.held(Keys::F12));
+    }
+
+    #[test]
+    fn presses_and_releases_are_edge_triggered() {
+        let mut state = InputState::new();
+
+        state.press(Keys::Space);
+        assert!(state.just_pressed(Keys::Space));
+
+        state.advance(Duration::from_millis(16));
+        assert!(state.held(Keys::Space));
+        assert!(!state.just_pressed(Keys::Space));
+
+        state.release(Keys::Space);
+        assert!(state.just_released(Keys::Space));
+
+        state.advance(Duration::from_millis(16));
+        assert!(!state.just_released(Keys::Space));
+    }
+
+    #[test]
+    fn taps_within_a_frame_are_not_lost() {
+        let mut state = InputState::new();
+        state.press(Keys::Space);
+        state.release(Keys::Space);
+        state.press_button(MouseButtons::Left);
+        state.release_button(MouseButtons::Left);
+        assert!(state.just_pressed(Keys::Space) && state.button_just_pressed(MouseButtons::Left));
+
+        state.advance(Duration::from_millis(16));
+        assert!(state.just_released(Keys::Space) && state.button_just_released(MouseButtons::Left));
+        assert!(!state.held(Keys::Space) && !state.button_held(MouseButtons::Left));
+
+        state.advance(Duration::from_millis(16));
+        assert!(!state.just_released(Keys::Space));
+    }
+
+    #[test]
+    fn hold_duration_accumulates_while_held() {
+        let mut state = InputState::new();
+        state.press(Keys::A);
+        assert_eq!(state.hold_duration(Keys::A), Duration::new(0, 0));
+
+        state.advance(Duration::from_millis(10));
+        state.advance(Duration::from_millis(20));
+        assert_eq!(state.hold_duration(Keys::A), Duration::from_millis(30));
+
+        state.release(Keys::A);
+        assert_eq!(state.hold_duration(Keys::A), Duration::new(0, 0));
+        state.advance(Duration::from_millis(10));
+        state.press(Keys::A);
+        assert_eq!(state.hold_duration(Keys::A), Duration::new(0, 0));
+    }
+
+    #[test]
+    fn mouse_buttons_are_edge_triggered() {
+        let mut state = InputState::new();
+        assert_eq!(MouseButtons::from_mouse_button(MouseButton::Other(4)), None);
+
+        state.press_button(MouseButtons::Left);
+        assert!(state.button_just_pressed(MouseButtons::Left));
+        assert!(!state.button_held(MouseButtons::Right));
+
+        state.advance(Duration::from_millis(16));
+        assert!(state.button_held(MouseButtons::Left));
+        assert!(!state.button_just_pressed(MouseButtons::Left));
+
+        state.release_button(MouseButtons::Left);
+        assert!(state.button_just_released(MouseButtons::Left));
+    }
+
+    #[test]
+    fn cursor_is_mapped_to_world_coordinates() {
+        let mut state = InputState::new();
+        let mut camera = Camera::new();
+        camera.resize(200.0, 100.0);
+        assert_eq!(state.cursor_world(&camera), None);
+
+        state.move_cursor(150.0, 25.0);
+        assert_eq!(state.cursor(), Some((150.0, 25.0)));
+        assert_eq!(state.cursor_world(&camera), Some((50.0, 25.0)));
+    }
+
+    #[test]
+    fn scrolling_accumulates_within_a_frame() {
+        let mut state = InputState::new();
+
+        state.scroll(0.0, 1.0);
+        state.scroll(0.5, 2.0);
+        assert_eq!(state.scroll_delta(), (0.5, 3.0));
+
+        state.advance(Duration::from_millis(16));
+        assert_eq!(state.scroll_delta(), (0.0, 0.0));
     }
 }
@@ -0,0 +1,22 @@
+//! An entity-component-system game engine built on winit
+
+extern crate winit;
+
+pub mod action;
+pub mod app;
+pub mod camera;
+pub mod command;
+pub mod command_buffer;
+pub mod component;
+pub mod entity;
+pub mod event;
+pub mod hierarchy;
+pub mod input;
+pub mod query;
+pub mod replay;
+pub mod resource;
+pub mod storage;
+pub mod system;
+pub mod time;
+pub mod util;
+pub mod world;
@@ -0,0 +1,719 @@
+use component::Component;
+use entity::Entity;
+use storage::{ChangeTicks, DistinctStorage, ReadStorage, Storage, StorageMut, Tick, WriteStorage};
+use storage::archetype::ArchetypeId;
+use util::BitVector;
+use world::World;
+
+use std::vec::IntoIter;
+
+/// A set of storages whose components can be iterated together, entity by entity
+///
+/// Implemented for references to `DistinctStorage`s and for tuples of joins, so any number of
+/// storages borrowed from a `World` can be combined:
+///
+/// ```ignore
+/// let (mut positions, velocities, colliders, frozen) =
+///     (world.write::<Position>(), world.read::<Velocity>(), world.read::<Collider>(), world.read::<Frozen>());
+///
+/// for (entity, (position, velocity, collider, ())) in
+///     (&mut positions, &velocities, Maybe(&colliders), Without(&frozen)).join() { ... }
+/// ```
+///
+/// # Safety
+///
+/// `entities` must never list an entity twice, since `join` fetches every entity it lists while
+/// the items fetched before are still alive.
+pub unsafe trait Join: Sized {
+    type Item;
+
+    /// The number of entities this join can match at most, or `None` if it does not restrict them
+    fn size_hint(&self) -> Option<usize>;
+
+    /// The entities to drive iteration from, taken from the most restrictive storage
+    fn entities(&self) -> Option<Vec<Entity>>;
+
+    /// Fetches the item for an entity, if it matches
+    ///
+    /// # Safety
+    ///
+    /// Callers must not fetch the same entity twice while a previously fetched item is alive.
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item>;
+
+    /// Iterates the entities matched by every part of this join
+    ///
+    /// Panics if no part of the join restricts the entities, e.g. when every part is optional.
+    fn join(self) -> JoinIter<Self> {
+        let entities = self.entities().expect("a join needs at least one required storage");
+        JoinIter { join: self, entities: entities.into_iter() }
+    }
+
+    /// Iterates the entities whose signatures fit a mask and that every part of this join matches
+    ///
+    /// The entities come from `World::matching` rather than from scanning a storage.
+    fn join_matching(self, world: &World, mask: &Mask) -> JoinIter<Self> {
+        JoinIter { join: self, entities: world.matching(mask).into_iter() }
+    }
+}
+
+pub struct JoinIter<J> {
+    join: J,
+    entities: IntoIter<Entity>,
+}
+
+impl<J: Join> Iterator for JoinIter<J> {
+    type Item = (Entity, J::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entity in &mut self.entities {
+            // SAFETY: `Join` promises that `entities` never repeats, and `World::matching` lists
+            // one signature per slot, so each entity is fetched exactly once
+            if let Some(item) = unsafe { self.join.fetch(entity) } {
+                return Some((entity, item));
+            }
+        }
+
+        None
+    }
+}
+
+// SAFETY: `DistinctStorage` lists every entity once
+unsafe impl<'a, S: DistinctStorage> Join for &'a S
+where
+    S::Component: 'a,
+{
+    type Item = &'a S::Component;
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn entities(&self) -> Option<Vec<Entity>> {
+        Some(Storage::entities(*self))
+    }
+
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+        self.get(entity)
+    }
+}
+
+// SAFETY: as for `&S`
+unsafe impl<'a, S: StorageMut + DistinctStorage> Join for &'a mut S
+where
+    S::Component: 'a,
+{
+    type Item = &'a mut S::Component;
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn entities(&self) -> Option<Vec<Entity>> {
+        Some(Storage::entities(&**self))
+    }
+
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+        // SAFETY: the caller fetches each entity at most once, and `DistinctStorage` promises
+        // that different entities never share a component
+        let storage: *mut S = &mut **self;
+        (*storage).get_mut(entity)
+    }
+}
+
+/// Joins a storage optionally, yielding `None` for entities without the component
+pub struct Maybe<J>(pub J);
+
+// SAFETY: lists no entities
+unsafe impl<J: Join> Join for Maybe<J> {
+    type Item = Option<J::Item>;
+
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    fn entities(&self) -> Option<Vec<Entity>> {
+        None
+    }
+
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+        Some(self.0.fetch(entity))
+    }
+}
+
+/// Excludes the entities that have a component in a storage
+pub struct Without<J>(pub J);
+
+// SAFETY: lists no entities
+unsafe impl<J: Join> Join for Without<J> {
+    type Item = ();
+
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    fn entities(&self) -> Option<Vec<Entity>> {
+        None
+    }
+
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+        match self.0.fetch(entity) {
+            Some(_) => None,
+            None => Some(()),
+        }
+    }
+}
+
+/// A join over storages kept in archetype tables, which visits a whole table at a time
+///
+/// The tables of one archetype list the same entities in the same order, so the slices lent out
+/// for an archetype line up row by row and can be zipped without looking entities up:
+///
+/// ```ignore
+/// for (_, (positions, velocities)) in (&mut world.write::<Position>(), &world.read::<Velocity>()).join_tables() {
+///     for (position, velocity) in positions.iter_mut().zip(velocities) { ... }
+/// }
+/// ```
+///
+/// Only an archetype `World` keeps its components in tables; joining the tables of a component
+/// kept in its own storage panics.
+///
+/// # Safety
+///
+/// `archetypes` must never list an archetype twice, for the same reason as `Join::entities`.
+pub unsafe trait TableJoin: Sized {
+    type Table;
+
+    /// The archetypes to drive iteration from, or `None` if this join does not restrict them
+    fn archetypes(&self) -> Option<Vec<ArchetypeId>>;
+
+    /// Whether every part of this join accepts an archetype, checked before any table is lent out
+    fn matches(&self, archetype: ArchetypeId) -> bool;
+
+    /// Lends out the rows of an archetype's tables, if the archetype matches
+    ///
+    /// # Safety
+    ///
+    /// Callers must not fetch the same archetype twice while a previously fetched table is alive.
+    unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table>;
+
+    /// Iterates the archetypes matched by every part of this join, table by table
+    ///
+    /// Panics if no part of the join restricts the archetypes.
+    fn join_tables(self) -> TableIter<Self> {
+        let archetypes = self.archetypes().expect("a join needs at least one required storage");
+        TableIter { join: self, archetypes: archetypes.into_iter() }
+    }
+}
+
+pub struct TableIter<J> {
+    join: J,
+    archetypes: IntoIter<ArchetypeId>,
+}
+
+impl<J: TableJoin> Iterator for TableIter<J> {
+    type Item = (ArchetypeId, J::Table);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for archetype in &mut self.archetypes {
+            // SAFETY: `TableJoin` promises that `archetypes` never repeats, so each is fetched
+            // exactly once
+            if !self.join.matches(archetype) {
+                continue;
+            }
+
+            if let Some(table) = unsafe { self.join.table(archetype) } {
+                return Some((archetype, table));
+            }
+        }
+
+        None
+    }
+}
+
+// SAFETY: an archetype storage has one table per archetype
+unsafe impl<'a, 'b, T: Component> TableJoin for &'a ReadStorage<'b, T> {
+    type Table = &'a [T];
+
+    fn archetypes(&self) -> Option<Vec<ArchetypeId>> {
+        Some(self.archetype_storage().tables().map(|(archetype, _, _)| archetype).collect())
+    }
+
+    fn matches(&self, archetype: ArchetypeId) -> bool {
+        self.archetype_storage().table(archetype).is_some()
+    }
+
+    unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table> {
+        let storage = *self;
+        storage.archetype_storage().table(archetype).map(|(_, components)| components)
+    }
+}
+
+// SAFETY: as for `&ReadStorage`
+unsafe impl<'a, 'b, T: Component> TableJoin for &'a WriteStorage<'b, T> {
+    type Table = &'a [T];
+
+    fn archetypes(&self) -> Option<Vec<ArchetypeId>> {
+        Some(self.archetype_storage().tables().map(|(archetype, _, _)| archetype).collect())
+    }
+
+    fn matches(&self, archetype: ArchetypeId) -> bool {
+        self.archetype_storage().table(archetype).is_some()
+    }
+
+    unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table> {
+        let storage = *self;
+        storage.archetype_storage().table(archetype).map(|(_, components)| components)
+    }
+}
+
+// SAFETY: as for `&ReadStorage`
+/// Records the storage's tick as the change tick of every entity in a table it lends out
+unsafe impl<'a, 'b, T: Component> TableJoin for &'a mut WriteStorage<'b, T> {
+    type Table = &'a mut [T];
+
+    fn archetypes(&self) -> Option<Vec<ArchetypeId>> {
+        Some(self.archetype_storage().tables().map(|(archetype, _, _)| archetype).collect())
+    }
+
+    fn matches(&self, archetype: ArchetypeId) -> bool {
+        self.archetype_storage().table(archetype).is_some()
+    }
+
+    unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table> {
+        // SAFETY: the caller fetches each archetype at most once, and different archetypes have
+        // different tables
+        let storage: *mut WriteStorage<'b, T> = &mut **self;
+        let tick = (*storage).tick();
+        let (tables, ticks) = (*storage).archetype_storage_mut();
+        let (entities, components) = tables.table_mut(archetype)?;
+        for &entity in entities {
+            ticks.mark_changed(entity, tick);
+        }
+
+        Some(components)
+    }
+}
+
+// SAFETY: lists no archetypes
+unsafe impl<J: TableJoin> TableJoin for Maybe<J> {
+    type Table = Option<J::Table>;
+
+    fn archetypes(&self) -> Option<Vec<ArchetypeId>> {
+        None
+    }
+
+    fn matches(&self, _: ArchetypeId) -> bool {
+        true
+    }
+
+    unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table> {
+        Some(self.0.table(archetype))
+    }
+}
+
+// SAFETY: lists no archetypes
+unsafe impl<J: TableJoin> TableJoin for Without<J> {
+    type Table = ();
+
+    fn archetypes(&self) -> Option<Vec<ArchetypeId>> {
+        None
+    }
+
+    fn matches(&self, archetype: ArchetypeId) -> bool {
+        !self.0.matches(archetype)
+    }
+
+    unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table> {
+        if self.0.matches(archetype) { None } else { Some(()) }
+    }
+}
+
+/// A join over a storage borrowed from a `World`, which records when its components changed
+pub trait Tracked: Join {
+    fn change_ticks(&self) -> &ChangeTicks;
+}
+
+impl<'a, 'b, T: Component> Tracked for &'a ReadStorage<'b, T> {
+    fn change_ticks(&self) -> &ChangeTicks {
+        ReadStorage::change_ticks(self)
+    }
+}
+
+impl<'a, 'b, T: Component> Tracked for &'a WriteStorage<'b, T> {
+    fn change_ticks(&self) -> &ChangeTicks {
+        WriteStorage::change_ticks(self)
+    }
+}
+
+impl<'a, 'b, T: Component> Tracked for &'a mut WriteStorage<'b, T> {
+    fn change_ticks(&self) -> &ChangeTicks {
+        WriteStorage::change_ticks(self)
+    }
+}
+
+/// Joins only the components inserted after a tick
+///
+/// A system can pass the `last_run` tick the schedule gives it to see only the components added
+/// since its previous run.
+pub struct Added<J>(pub J, pub Tick);
+
+// SAFETY: lists some of the entities of the join it wraps
+unsafe impl<J: Tracked> Join for Added<J> {
+    type Item = J::Item;
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+
+    fn entities(&self) -> Option<Vec<Entity>> {
+        let ticks = self.0.change_ticks();
+        self.0.entities().map(|entities| entities.into_iter().filter(|&entity| ticks.is_added_since(entity, self.1)).collect())
+    }
+
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+        if !self.0.change_ticks().is_added_since(entity, self.1) {
+            return None;
+        }
+
+        self.0.fetch(entity)
+    }
+}
+
+/// Joins only the components inserted or mutably accessed after a tick
+///
+/// Like `Added`, the tick is usually the `last_run` tick a system is given.
+pub struct Changed<J>(pub J, pub Tick);
+
+// SAFETY: as for `Added`
+unsafe impl<J: Tracked> Join for Changed<J> {
+    type Item = J::Item;
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+
+    fn entities(&self) -> Option<Vec<Entity>> {
+        let ticks = self.0.change_ticks();
+        self.0.entities().map(|entities| entities.into_iter().filter(|&entity| ticks.is_changed_since(entity, self.1)).collect())
+    }
+
+    unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+        if !self.0.change_ticks().is_changed_since(entity, self.1) {
+            return None;
+        }
+
+        self.0.fetch(entity)
+    }
+}
+
+/// Component types whose signature bits can be combined, a tuple of any of them or `()`
+pub trait ComponentSet {
+    /// The bits of these component types in the world's entity signatures
+    ///
+    /// Panics if a type is not registered.
+    fn signature(world: &World) -> BitVector;
+}
+
+impl ComponentSet for () {
+    fn signature(_: &World) -> BitVector {
+        BitVector::new()
+    }
+}
+
+/// The component types an entity's signature must include and those it must lack
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mask {
+    pub required: BitVector,
+    pub excluded: BitVector,
+}
+
+impl Mask {
+    pub fn matches(&self, signature: &BitVector) -> bool {
+        self.required.is_subset(signature) && self.excluded.is_disjoint(signature)
+    }
+}
+
+macro_rules! impl_component_set_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Component),+> ComponentSet for ($($name,)+) {
+            fn signature(world: &World) -> BitVector {
+                let mut signature = BitVector::new();
+                $(signature.set(world.component_bit::<$name>());)+
+                signature
+            }
+        }
+    };
+}
+
+impl_component_set_for_tuple!(A);
+impl_component_set_for_tuple!(A, B);
+impl_component_set_for_tuple!(A, B, C);
+impl_component_set_for_tuple!(A, B, C, D);
+impl_component_set_for_tuple!(A, B, C, D, E);
+impl_component_set_for_tuple!(A, B, C, D, E, F);
+impl_component_set_for_tuple!(A, B, C, D, E, F, G);
+impl_component_set_for_tuple!(A, B, C, D, E, F, G, H);
+
+macro_rules! impl_join_for_tuple {
+    ($($name:ident),+) => {
+        // SAFETY: lists the entities of one of its parts
+        #[allow(non_snake_case)]
+        unsafe impl<$($name: Join),+> Join for ($($name,)+) {
+            type Item = ($($name::Item,)+);
+
+            fn size_hint(&self) -> Option<usize> {
+                let ($(ref $name,)+) = *self;
+                [$($name.size_hint()),+].iter().filter_map(|hint| *hint).min()
+            }
+
+            #[allow(unused_assignments)]
+            fn entities(&self) -> Option<Vec<Entity>> {
+                let ($(ref $name,)+) = *self;
+                let hints = [$($name.size_hint()),+];
+                let smallest = hints.iter().enumerate()
+                    .filter_map(|(position, hint)| hint.map(|size| (size, position)))
+                    .min()
+                    .map(|(_, position)| position)?;
+
+                let mut position = 0;
+                $(
+                    if position == smallest {
+                        return $name.entities();
+                    }
+                    position += 1;
+                )+
+
+                unreachable!()
+            }
+
+            unsafe fn fetch(&mut self, entity: Entity) -> Option<Self::Item> {
+                let ($(ref mut $name,)+) = *self;
+                Some(($($name.fetch(entity)?,)+))
+            }
+        }
+    };
+}
+
+impl_join_for_tuple!(A);
+impl_join_for_tuple!(A, B);
+impl_join_for_tuple!(A, B, C);
+impl_join_for_tuple!(A, B, C, D);
+impl_join_for_tuple!(A, B, C, D, E);
+impl_join_for_tuple!(A, B, C, D, E, F);
+impl_join_for_tuple!(A, B, C, D, E, F, G);
+impl_join_for_tuple!(A, B, C, D, E, F, G, H);
+
+macro_rules! impl_table_join_for_tuple {
+    ($($name:ident),+) => {
+        // SAFETY: lists the archetypes of one of its parts
+        #[allow(non_snake_case)]
+        unsafe impl<$($name: TableJoin),+> TableJoin for ($($name,)+) {
+            type Table = ($($name::Table,)+);
+
+            fn archetypes(&self) -> Option<Vec<ArchetypeId>> {
+                let ($(ref $name,)+) = *self;
+                vec![$($name.archetypes()),+].into_iter().flatten().min_by_key(Vec::len)
+            }
+
+            fn matches(&self, archetype: ArchetypeId) -> bool {
+                let ($(ref $name,)+) = *self;
+                $($name.matches(archetype))&&+
+            }
+
+            unsafe fn table(&mut self, archetype: ArchetypeId) -> Option<Self::Table> {
+                let ($(ref mut $name,)+) = *self;
+                Some(($($name.table(archetype)?,)+))
+            }
+        }
+    };
+}
+
+impl_table_join_for_tuple!(A);
+impl_table_join_for_tuple!(A, B);
+impl_table_join_for_tuple!(A, B, C);
+impl_table_join_for_tuple!(A, B, C, D);
+impl_table_join_for_tuple!(A, B, C, D, E);
+impl_table_join_for_tuple!(A, B, C, D, E, F);
+impl_table_join_for_tuple!(A, B, C, D, E, F, G);
+impl_table_join_for_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::StorageInsert;
+    use storage::map::MapStorage;
+    use storage::sequence::SequenceStorage;
+
+    fn storages() -> (SequenceStorage<i32>, SequenceStorage<u8>, MapStorage<char>) {
+        let mut numbers = SequenceStorage::new();
+        let mut bytes = SequenceStorage::new();
+        let mut chars = MapStorage::new();
+
+        for index in 0..4 {
+            numbers.insert(Entity::new(index, 0), index as i32);
+        }
+
+        bytes.insert(Entity::new(1, 0), 1);
+        bytes.insert(Entity::new(2, 0), 2);
+        bytes.insert(Entity::new(3, 0), 3);
+        chars.insert(Entity::new(2, 0), 'c');
+
+        (numbers, bytes, chars)
+    }
+
+    #[test]
+    fn joins_more_than_two_storages() {
+        let (mut numbers, bytes, chars) = storages();
+
+        for (_, (number, byte, _)) in (&mut numbers, &bytes, &chars).join() {
+            *number += *byte as i32;
+        }
+
+        assert_eq!(numbers.as_slice(), [Some(0), Some(1), Some(4), Some(3)]);
+    }
+
+    #[test]
+    fn optional_storages_do_not_filter() {
+        let (numbers, bytes, chars) = storages();
+
+        let mut joined: Vec<_> = (&numbers, &bytes, Maybe(&chars)).join()
+            .map(|(entity, (_, _, chr))| (entity.index(), chr.cloned()))
+            .collect();
+        joined.sort();
+
+        assert_eq!(joined, [(1, None), (2, Some('c')), (3, None)]);
+    }
+
+    #[test]
+    fn excluded_storages_filter_out_entities() {
+        let (numbers, bytes, chars) = storages();
+
+        let joined: Vec<_> = (&numbers, Without(&bytes)).join().map(|(entity, _)| entity.index()).collect();
+        assert_eq!(joined, [0]);
+
+        let joined: Vec<_> = (&bytes, Without(&chars)).join().map(|(entity, _)| entity.index()).collect();
+        assert_eq!(joined, [1, 3]);
+    }
+
+    #[test]
+    fn iteration_is_driven_by_smallest_storage() {
+        let (numbers, bytes, chars) = storages();
+
+        assert_eq!((&numbers, &bytes, &chars).size_hint(), Some(1));
+        assert_eq!((&numbers, &bytes, &chars).entities(), Some(vec![Entity::new(2, 0)]));
+        assert_eq!((&numbers, Maybe(&chars)).entities(), Some(numbers.entities()));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one required storage")]
+    fn join_without_required_storage_panics() {
+        let (_, _, chars) = storages();
+        (Maybe(&chars),).join();
+    }
+
+    #[test]
+    fn change_filters_skip_untouched_components() {
+        use component::{Position, Velocity};
+        use world::World;
+
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        let still = world.create_entity().with_component(Position(0.0, 0.0)).with_component(Velocity(0.0, 0.0)).build();
+        let moving = world.create_entity().with_component(Position(0.0, 0.0)).with_component(Velocity(1.0, 0.0)).build();
+        let last_run = world.change_tick();
+
+        world.advance_change_tick();
+        for (_, (position, velocity)) in (&mut world.write::<Position>(), &world.read::<Velocity>()).join() {
+            if velocity.0 != 0.0 {
+                position.0 += velocity.0;
+            }
+        }
+
+        let spawned = world.create_entity().with_component(Position(5.0, 5.0)).build();
+
+        let positions = world.read::<Position>();
+        let changed: Vec<_> = Changed(&positions, last_run).join().map(|(entity, _)| entity).collect();
+        let added: Vec<_> = Added(&positions, last_run).join().map(|(entity, _)| entity).collect();
+        let all: Vec<_> = Added(&positions, 0).join().map(|(entity, _)| entity).collect();
+
+        // Mutable joins mark every component they fetch, whether or not it was written to
+        assert_eq!(changed, [still, moving, spawned]);
+        assert_eq!(added, [spawned]);
+        assert_eq!(all, [still, moving, spawned]);
+
+        let velocities = world.read::<Velocity>();
+        assert_eq!((&velocities, Changed(&positions, world.change_tick() - 1)).join().count(), 2);
+    }
+
+    #[test]
+    fn masks_select_entities_by_signature() {
+        use component::{Position, Velocity};
+
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        let moving = world.create_entity().with_component(Position(0.0, 0.0)).with_component(Velocity(1.0, 0.0)).build();
+        let still = world.create_entity().with_component(Position(0.0, 0.0)).build();
+        world.create_entity().with_component(Velocity(1.0, 0.0)).build();
+
+        assert_eq!(world.matching(&world.mask::<(Position, Velocity), ()>()), [moving]);
+        assert_eq!(world.matching(&world.mask::<(Position,), (Velocity,)>()), [still]);
+
+        world.remove_component::<Velocity>(moving);
+        let mask = world.mask::<(Position,), (Velocity,)>();
+        let (mut positions, velocities) = (world.write::<Position>(), world.read::<Velocity>());
+        for (_, (position, ())) in (&mut positions, Without(&velocities)).join_matching(&world, &mask) {
+            position.1 += 1.0;
+        }
+
+        assert_eq!(positions.get(moving), Some(&Position(0.0, 1.0)));
+        assert_eq!(positions.get(still), Some(&Position(0.0, 1.0)));
+    }
+
+    #[test]
+    fn table_joins_walk_lined_up_rows() {
+        use component::{Position, Velocity};
+        use world::{StorageBackend, World};
+
+        struct Frozen;
+
+        impl Component for Frozen {
+            type Storage = MapStorage<Self>;
+        }
+
+        let mut world = World::with_backend(StorageBackend::Archetype);
+        world.register::<Position>();
+        world.register::<Velocity>();
+        world.register::<Frozen>();
+        let mut moving = Vec::new();
+        for index in 0..4 {
+            let entity = world.create_entity().with_component(Position(0.0, 0.0)).with_component(Velocity(index as f64, 0.0)).build();
+            moving.push(entity);
+        }
+
+        world.insert_component(moving[1], Frozen);
+        world.destroy_entity(moving[0]);
+        world.insert_component(moving[2], Velocity(5.0, 0.0));
+        let still = world.create_entity().with_component(Position(9.0, 9.0)).build();
+        let tick = world.advance_change_tick();
+
+        {
+            let (mut positions, velocities, frozen) = (world.write::<Position>(), world.read::<Velocity>(), world.read::<Frozen>());
+            for (_, (positions, velocities, ())) in (&mut positions, &velocities, Without(&frozen)).join_tables() {
+                for (position, velocity) in positions.iter_mut().zip(velocities) {
+                    position.0 += velocity.0;
+                }
+            }
+        }
+
+        let positions = world.read::<Position>();
+        let xs: Vec<_> = moving.iter().map(|entity| positions.get(*entity).map(|position| position.0)).collect();
+        assert_eq!(xs, [None, Some(0.0), Some(5.0), Some(3.0)]);
+        assert_eq!(positions.get(still), Some(&Position(9.0, 9.0)));
+
+        let changed: Vec<_> = Changed(&positions, tick - 1).join().map(|(entity, _)| entity).collect();
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains(&moving[2]) && changed.contains(&moving[3]));
+    }
+}
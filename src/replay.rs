@@ -0,0 +1,248 @@
+use input::{InputEvent, Keys, MouseButtons};
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+const MAGIC: &[u8; 4] = b"HKRP";
+const VERSION: u8 = 1;
+
+/// Component state that contributes to `World::state_hash`
+///
+/// Implementations must feed the hasher the exact bits of their data, so that two states
+/// hash the same only if they are bit-identical.
+pub trait StateHash {
+    fn hash_state(&self, hasher: &mut StateHasher);
+}
+
+/// A 64-bit FNV-1a hasher, which unlike `DefaultHasher` is stable across builds
+#[derive(Clone, Copy, Debug)]
+pub struct StateHasher(u64);
+
+impl StateHasher {
+    pub fn new() -> Self {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// The input events of one simulation tick and the world's state hash after it ran
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tick {
+    pub events: Vec<InputEvent>,
+    pub hash: u64,
+}
+
+/// Every tick of a run, recorded so the run can be replayed exactly
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    step: Duration,
+    ticks: Vec<Tick>,
+}
+
+impl Recording {
+    /// Creates an empty recording of a loop that advances by `step` every tick
+    pub fn new(step: Duration) -> Self {
+        Self { step, ticks: Vec::new() }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn ticks(&self) -> &[Tick] {
+        &self.ticks
+    }
+
+    pub fn push(&mut self, events: Vec<InputEvent>, hash: u64) {
+        self.ticks.push(Tick { events, hash });
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Encodes the recording as a header followed by each tick's hash and events
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&(self.step.as_nanos() as u64).to_le_bytes())?;
+        writer.write_all(&(self.ticks.len() as u64).to_le_bytes())?;
+
+        for tick in &self.ticks {
+            writer.write_all(&tick.hash.to_le_bytes())?;
+            writer.write_all(&(tick.events.len() as u32).to_le_bytes())?;
+            for event in &tick.events {
+                write_event(writer, event)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u8(reader)? != VERSION {
+            return Err(invalid("not a recording, or one from an unsupported version"));
+        }
+
+        let mut recording = Self::new(Duration::from_nanos(read_u64(reader)?));
+        for _ in 0..read_u64(reader)? {
+            let hash = read_u64(reader)?;
+            let events = (0..read_u32(reader)?)
+                .map(|_| read_event(reader))
+                .collect::<io::Result<_>>()?;
+
+            recording.push(events, hash);
+        }
+
+        Ok(recording)
+    }
+}
+
+/// The first tick at which a replay did not reproduce the recorded state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: usize,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "replay diverged at tick {}: expected state {:016x}, found {:016x}", self.tick, self.expected, self.actual)
+    }
+}
+
+impl Error for Divergence {}
+
+fn write_event<W: Write>(writer: &mut W, event: &InputEvent) -> io::Result<()> {
+    match *event {
+        InputEvent::KeyPressed(key) => writer.write_all(&[0, key as u8]),
+        InputEvent::KeyReleased(key) => writer.write_all(&[1, key as u8]),
+        InputEvent::ButtonPressed(button) => writer.write_all(&[2, button as u8]),
+        InputEvent::ButtonReleased(button) => writer.write_all(&[3, button as u8]),
+        InputEvent::CursorMoved(x, y) => write_pair(writer, 4, x, y),
+        InputEvent::CursorLeft => writer.write_all(&[5]),
+        InputEvent::Scrolled(x, y) => write_pair(writer, 6, x, y),
+        InputEvent::Resized(width, height) => write_pair(writer, 7, width, height),
+    }
+}
+
+fn write_pair<W: Write>(writer: &mut W, tag: u8, x: f64, y: f64) -> io::Result<()> {
+    writer.write_all(&[tag])?;
+    writer.write_all(&x.to_bits().to_le_bytes())?;
+    writer.write_all(&y.to_bits().to_le_bytes())
+}
+
+fn read_event<R: Read>(reader: &mut R) -> io::Result<InputEvent> {
+    let key = |index: u8| Keys::ALL.get(index as usize).cloned().ok_or_else(|| invalid("unknown key"));
+    let button = |index: u8| MouseButtons::ALL.get(index as usize).cloned().ok_or_else(|| invalid("unknown button"));
+
+    Ok(match read_u8(reader)? {
+        0 => InputEvent::KeyPressed(key(read_u8(reader)?)?),
+        1 => InputEvent::KeyReleased(key(read_u8(reader)?)?),
+        2 => InputEvent::ButtonPressed(button(read_u8(reader)?)?),
+        3 => InputEvent::ButtonReleased(button(read_u8(reader)?)?),
+        4 => InputEvent::CursorMoved(read_f64(reader)?, read_f64(reader)?),
+        5 => InputEvent::CursorLeft,
+        6 => InputEvent::Scrolled(read_f64(reader)?, read_f64(reader)?),
+        7 => InputEvent::Resized(read_f64(reader)?, read_f64(reader)?),
+        _ => return Err(invalid("unknown event")),
+    })
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+    read_u64(reader).map(f64::from_bits)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recordings_survive_a_round_trip() {
+        let mut recording = Recording::new(Duration::from_millis(16));
+        recording.push(vec![InputEvent::KeyPressed(Keys::W), InputEvent::CursorMoved(1.5, -2.0)], 7);
+        recording.push(Vec::new(), 8);
+        recording.push(vec![InputEvent::ButtonReleased(MouseButtons::Middle), InputEvent::CursorLeft], 9);
+
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+
+        assert_eq!(Recording::read_from(&mut &bytes[..]).unwrap(), recording);
+    }
+
+    #[test]
+    fn corrupt_recordings_are_rejected() {
+        let mut bytes = Vec::new();
+        Recording::new(Duration::from_millis(16)).write_to(&mut bytes).unwrap();
+        bytes[0] = b'X';
+
+        assert_eq!(Recording::read_from(&mut &bytes[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn state_hasher_is_order_sensitive() {
+        let mut first = StateHasher::new();
+        first.write_u32(1);
+        first.write_u32(2);
+
+        let mut second = StateHasher::new();
+        second.write_u32(2);
+        second.write_u32(1);
+
+        assert_ne!(first.finish(), second.finish());
+    }
+}
@@ -0,0 +1,87 @@
+use std::any::{self, Any};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+/// Data of which a `World` holds a single instance, such as the input state or the frame time
+pub trait Resource: Any + Send + Sync {}
+
+impl<T: Any + Send + Sync> Resource for T {}
+
+type AnyResource = Box<dyn Any + Send + Sync>;
+
+/// A resource that can be borrowed from a shared `World`
+pub(crate) struct ResourceCell(RwLock<AnyResource>);
+
+impl ResourceCell {
+    pub(crate) fn new<R: Resource>(resource: R) -> Self {
+        ResourceCell(RwLock::new(Box::new(resource)))
+    }
+
+    pub(crate) fn into_inner<R: Resource>(self) -> R {
+        let resource = match self.0.into_inner() {
+            Ok(resource) => resource,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        *resource.downcast().expect("resource inserted under the wrong type")
+    }
+
+    pub(crate) fn read<R: Resource>(&self) -> Res<'_, R> {
+        let guard = match self.0.try_read() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                panic!("`{}` resource is already mutably borrowed", any::type_name::<R>())
+            },
+        };
+
+        Res { guard, marker: PhantomData }
+    }
+
+    pub(crate) fn write<R: Resource>(&self) -> ResMut<'_, R> {
+        let guard = match self.0.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                panic!("`{}` resource is already borrowed", any::type_name::<R>())
+            },
+        };
+
+        ResMut { guard, marker: PhantomData }
+    }
+}
+
+/// Shared access to a resource borrowed from a `World`
+pub struct Res<'a, R: Resource> {
+    guard: RwLockReadGuard<'a, AnyResource>,
+    marker: PhantomData<R>,
+}
+
+impl<'a, R: Resource> Deref for Res<'a, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.guard.downcast_ref().expect("resource inserted under the wrong type")
+    }
+}
+
+/// Exclusive access to a resource borrowed from a `World`
+pub struct ResMut<'a, R: Resource> {
+    guard: RwLockWriteGuard<'a, AnyResource>,
+    marker: PhantomData<R>,
+}
+
+impl<'a, R: Resource> Deref for ResMut<'a, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.guard.downcast_ref().expect("resource inserted under the wrong type")
+    }
+}
+
+impl<'a, R: Resource> DerefMut for ResMut<'a, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        self.guard.downcast_mut().expect("resource inserted under the wrong type")
+    }
+}
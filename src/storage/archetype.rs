@@ -0,0 +1,313 @@
+use super::{AnyStorage, DistinctStorage, Storage, StorageMut};
+use component::Component;
+use entity::{Entity, Generation};
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Identifies a set of component types that some entities have exactly
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ArchetypeId(usize);
+
+impl ArchetypeId {
+    /// The archetype of entities without components
+    pub const EMPTY: ArchetypeId = ArchetypeId(0);
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Which archetype each entity of an archetype `World` belongs to
+pub struct Archetypes {
+    signatures: Vec<Vec<TypeId>>,
+    ids: HashMap<Vec<TypeId>, ArchetypeId>,
+    locations: Vec<Option<(Generation, ArchetypeId)>>,
+}
+
+impl Archetypes {
+    pub fn new() -> Self {
+        let mut ids = HashMap::new();
+        ids.insert(Vec::new(), ArchetypeId::EMPTY);
+
+        Self { signatures: vec![Vec::new()], ids, locations: Vec::new() }
+    }
+
+    /// The number of archetypes seen so far, including the empty one
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Whether no archetype but the empty one has been seen
+    pub fn is_empty(&self) -> bool {
+        self.signatures.len() == 1
+    }
+
+    /// The sorted component types of an archetype
+    pub fn signature(&self, archetype: ArchetypeId) -> &[TypeId] {
+        &self.signatures[archetype.0]
+    }
+
+    pub fn archetype_of(&self, entity: Entity) -> ArchetypeId {
+        match self.locations.get(entity.index()) {
+            Some(Some((generation, archetype))) if *generation == entity.generation() => *archetype,
+            _ => ArchetypeId::EMPTY,
+        }
+    }
+
+    /// The archetype with the same components as `archetype` plus `component`
+    pub fn with(&mut self, archetype: ArchetypeId, component: TypeId) -> ArchetypeId {
+        let mut signature = self.signatures[archetype.0].clone();
+        if let Err(position) = signature.binary_search(&component) {
+            signature.insert(position, component);
+        }
+
+        self.id(signature)
+    }
+
+    /// The archetype with the same components as `archetype` except `component`
+    pub fn without(&mut self, archetype: ArchetypeId, component: TypeId) -> ArchetypeId {
+        let mut signature = self.signatures[archetype.0].clone();
+        signature.retain(|id| *id != component);
+
+        self.id(signature)
+    }
+
+    pub(crate) fn set(&mut self, entity: Entity, archetype: ArchetypeId) {
+        if self.locations.len() <= entity.index() {
+            self.locations.resize(entity.index() + 1, None);
+        }
+
+        self.locations[entity.index()] = Some((entity.generation(), archetype));
+    }
+
+    pub(crate) fn remove(&mut self, entity: Entity) {
+        if let Some(location) = self.locations.get_mut(entity.index()) {
+            *location = None;
+        }
+    }
+
+    fn id(&mut self, signature: Vec<TypeId>) -> ArchetypeId {
+        if let Some(id) = self.ids.get(&signature) {
+            return *id;
+        }
+
+        let id = ArchetypeId(self.signatures.len());
+        self.signatures.push(signature.clone());
+        self.ids.insert(signature, id);
+        id
+    }
+}
+
+impl Default for Archetypes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Table<T> {
+    entities: Vec<Entity>,
+    components: Vec<T>,
+}
+
+/// Stores components in tables, one per archetype, so that the components of entities with the
+/// same component types are packed together
+///
+/// Entities are placed by the `World`, which moves them between tables as they gain and lose
+/// components, so this is not a `StorageInsert`. Every storage of an archetype world makes the
+/// same moves, which keeps the rows of an archetype's tables lined up across component types.
+pub struct ArchetypeStorage<T> {
+    tables: Vec<Table<T>>,
+    rows: Vec<Option<(Generation, ArchetypeId, usize)>>,
+    len: usize,
+}
+
+impl<T> ArchetypeStorage<T> {
+    pub fn new() -> Self {
+        Self { tables: Vec::new(), rows: Vec::new(), len: 0 }
+    }
+
+    /// The entities and components of each archetype with this component, table by table
+    pub fn tables(&self) -> impl Iterator<Item = (ArchetypeId, &[Entity], &[T])> {
+        self.tables.iter().enumerate()
+            .filter(|(_, table)| !table.entities.is_empty())
+            .map(|(index, table)| (ArchetypeId(index), &table.entities[..], &table.components[..]))
+    }
+
+    pub fn tables_mut(&mut self) -> impl Iterator<Item = (ArchetypeId, &[Entity], &mut [T])> {
+        self.tables.iter_mut().enumerate()
+            .filter(|(_, table)| !table.entities.is_empty())
+            .map(|(index, table)| (ArchetypeId(index), &table.entities[..], &mut table.components[..]))
+    }
+
+    /// The entities and components in the table of an archetype, unless it is empty
+    pub fn table(&self, archetype: ArchetypeId) -> Option<(&[Entity], &[T])> {
+        match self.tables.get(archetype.0) {
+            Some(table) if !table.entities.is_empty() => Some((&table.entities[..], &table.components[..])),
+            _ => None,
+        }
+    }
+
+    pub fn table_mut(&mut self, archetype: ArchetypeId) -> Option<(&[Entity], &mut [T])> {
+        match self.tables.get_mut(archetype.0) {
+            Some(table) if !table.entities.is_empty() => Some((&table.entities[..], &mut table.components[..])),
+            _ => None,
+        }
+    }
+
+    /// Places the entity's component in the table of an archetype, replacing any it had
+    ///
+    /// A component replaced within the same archetype keeps its row.
+    pub fn insert_into(&mut self, entity: Entity, archetype: ArchetypeId, component: T) {
+        if let Some((current, row)) = self.location(entity) {
+            if current == archetype {
+                self.tables[archetype.0].components[row] = component;
+                return;
+            }
+        }
+
+        self.take(entity);
+
+        if self.tables.len() <= archetype.0 {
+            self.tables.resize_with(archetype.0 + 1, || Table { entities: Vec::new(), components: Vec::new() });
+        }
+
+        if self.rows.len() <= entity.index() {
+            self.rows.resize(entity.index() + 1, None);
+        }
+
+        let table = &mut self.tables[archetype.0];
+        self.rows[entity.index()] = Some((entity.generation(), archetype, table.entities.len()));
+        table.entities.push(entity);
+        table.components.push(component);
+        self.len += 1;
+    }
+
+    /// Removes the entity's component from its table, filling the gap with the table's last row
+    pub fn take(&mut self, entity: Entity) -> Option<T> {
+        let (archetype, row) = self.location(entity)?;
+        self.rows[entity.index()] = None;
+        self.len -= 1;
+
+        let table = &mut self.tables[archetype.0];
+        table.entities.swap_remove(row);
+        let component = table.components.swap_remove(row);
+
+        if let Some(moved) = table.entities.get(row) {
+            self.rows[moved.index()] = Some((moved.generation(), archetype, row));
+        }
+
+        Some(component)
+    }
+
+    fn location(&self, entity: Entity) -> Option<(ArchetypeId, usize)> {
+        match self.rows.get(entity.index()) {
+            Some(Some((generation, archetype, row))) if *generation == entity.generation() => Some((*archetype, *row)),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Default for ArchetypeStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Storage for ArchetypeStorage<T> {
+    type Component = T;
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.location(entity).map(|(archetype, row)| &self.tables[archetype.0].components[row])
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Every entity with the component, table by table, so joins visit each table in order
+    fn entities(&self) -> Vec<Entity> {
+        self.tables.iter().flat_map(|table| table.entities.iter().cloned()).collect()
+    }
+}
+
+impl<T> StorageMut for ArchetypeStorage<T> {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        let (archetype, row) = self.location(entity)?;
+        Some(&mut self.tables[archetype.0].components[row])
+    }
+}
+
+// SAFETY: every entity is in at most one row of one table
+unsafe impl<T> DistinctStorage for ArchetypeStorage<T> {}
+
+impl<T: Component> AnyStorage for ArchetypeStorage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.take(entity);
+    }
+
+    fn relocate(&mut self, entity: Entity, archetype: ArchetypeId) {
+        if let Some((current, _)) = self.location(entity) {
+            if current != archetype {
+                let component = self.take(entity).unwrap();
+                self.insert_into(entity, archetype, component);
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use component::Position;
+
+    #[test]
+    fn archetypes_are_shared_by_signature() {
+        let mut archetypes = Archetypes::new();
+        assert!(archetypes.is_empty());
+        let (first, second) = (TypeId::of::<u8>(), TypeId::of::<u16>());
+
+        let one = archetypes.with(ArchetypeId::EMPTY, first);
+        let both = archetypes.with(one, second);
+        let other = archetypes.with(ArchetypeId::EMPTY, second);
+        assert_eq!(archetypes.with(other, first), both);
+        assert_eq!(archetypes.without(both, second), one);
+        assert_eq!(archetypes.without(one, first), ArchetypeId::EMPTY);
+        assert_eq!(archetypes.len(), 4);
+        assert!(!archetypes.is_empty());
+    }
+
+    #[test]
+    fn removal_keeps_tables_packed() {
+        let mut storage = ArchetypeStorage::new();
+        let entities: Vec<_> = (0..3).map(|index| Entity::new(index, 0)).collect();
+        for (index, entity) in entities.iter().enumerate() {
+            storage.insert_into(*entity, ArchetypeId(1), index);
+        }
+
+        assert_eq!(storage.take(entities[0]), Some(0));
+        assert_eq!(storage.get(entities[2]), Some(&2));
+        assert_eq!(storage.tables().map(|(_, _, components)| components.to_vec()).collect::<Vec<_>>(), vec![vec![2, 1]]);
+    }
+
+    #[test]
+    fn relocation_moves_between_tables() {
+        let mut storage = ArchetypeStorage::new();
+        let entity = Entity::new(4, 1);
+        storage.insert_into(entity, ArchetypeId(1), Position(1.0, 0.0));
+        storage.insert_into(Entity::new(0, 0), ArchetypeId(1), Position(2.0, 0.0));
+
+        storage.relocate(entity, ArchetypeId(2));
+        assert_eq!(storage.get(entity), Some(&Position(1.0, 0.0)));
+        assert_eq!(storage.entities(), vec![Entity::new(0, 0), entity]);
+        assert_eq!(storage.len(), 2);
+    }
+}
@@ -0,0 +1,94 @@
+use entity::{Entity, Generation};
+
+/// A point in a world's history, advanced by the schedule around every stage
+pub type Tick = u64;
+
+/// When each entity's component was added and last changed
+#[derive(Clone, Debug, Default)]
+pub struct ChangeTicks {
+    ticks: Vec<Option<(Generation, Tick, Tick)>>,
+}
+
+impl ChangeTicks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tick at which the entity's component was inserted
+    pub fn added(&self, entity: Entity) -> Option<Tick> {
+        self.slot(entity).map(|&(_, added, _)| added)
+    }
+
+    /// The tick at which the entity's component was inserted or last mutably accessed
+    pub fn changed(&self, entity: Entity) -> Option<Tick> {
+        self.slot(entity).map(|&(_, _, changed)| changed)
+    }
+
+    pub fn is_added_since(&self, entity: Entity, since: Tick) -> bool {
+        self.added(entity).is_some_and(|added| added > since)
+    }
+
+    pub fn is_changed_since(&self, entity: Entity, since: Tick) -> bool {
+        self.changed(entity).is_some_and(|changed| changed > since)
+    }
+
+    pub(crate) fn mark_added(&mut self, entity: Entity, tick: Tick) {
+        if self.ticks.len() <= entity.index() {
+            self.ticks.resize(entity.index() + 1, None);
+        }
+
+        self.ticks[entity.index()] = Some((entity.generation(), tick, tick));
+    }
+
+    pub(crate) fn mark_changed(&mut self, entity: Entity, tick: Tick) {
+        match self.ticks.get_mut(entity.index()) {
+            Some(Some((generation, _, changed))) if *generation == entity.generation() => *changed = tick,
+            _ => self.mark_added(entity, tick),
+        }
+    }
+
+    pub(crate) fn clear(&mut self, entity: Entity) {
+        if self.slot(entity).is_some() {
+            self.ticks[entity.index()] = None;
+        }
+    }
+
+    fn slot(&self, entity: Entity) -> Option<&(Generation, Tick, Tick)> {
+        match self.ticks.get(entity.index()) {
+            Some(Some(slot)) if slot.0 == entity.generation() => Some(slot),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_keep_the_added_tick() {
+        let entity = Entity::new(2, 0);
+        let mut ticks = ChangeTicks::new();
+
+        ticks.mark_added(entity, 3);
+        ticks.mark_changed(entity, 5);
+        assert_eq!(ticks.added(entity), Some(3));
+        assert_eq!(ticks.changed(entity), Some(5));
+        assert!(ticks.is_added_since(entity, 2));
+        assert!(!ticks.is_added_since(entity, 3));
+        assert!(ticks.is_changed_since(entity, 4));
+    }
+
+    #[test]
+    fn ticks_belong_to_one_generation() {
+        let mut ticks = ChangeTicks::new();
+        ticks.mark_added(Entity::new(0, 0), 1);
+
+        assert_eq!(ticks.changed(Entity::new(0, 1)), None);
+        ticks.clear(Entity::new(0, 1));
+        assert_eq!(ticks.changed(Entity::new(0, 0)), Some(1));
+
+        ticks.clear(Entity::new(0, 0));
+        assert_eq!(ticks.changed(Entity::new(0, 0)), None);
+    }
+}
@@ -1,48 +1,127 @@
-use super::{Storage, StorageMut};
+use super::{AnyStorage, DistinctStorage, Storage, StorageInsert, StorageMut};
 use component::Component;
+use entity::{Entity, Generation};
 
+use std::any::Any;
 use std::collections::HashMap;
 use std::collections::hash_map::IterMut;
 
-pub struct MapStorage(HashMap<usize, Component>);
+/// Stores components in a hash map keyed by entity, best suited to components few entities have
+pub struct MapStorage<T>(HashMap<usize, (Generation, T)>);
 
-impl MapStorage {
+impl<T> MapStorage<T> {
     pub fn new() -> Self {
         MapStorage(HashMap::new())
     }
+}
+
+impl<T> Default for MapStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Storage for MapStorage<T> {
+    type Component = T;
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        match self.0.get(&entity.index()) {
+            Some((generation, component)) if *generation == entity.generation() => Some(component),
+            _ => None,
+        }
+    }
 
-    pub fn add(&mut self, index: usize, component: Component) {
-        self.0.insert(index, component);
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.0.iter().map(|(index, (generation, _))| Entity::new(*index, *generation)).collect()
     }
 }
 
-impl<'a> Storage<'a> for &'a MapStorage {
-    fn get(&self, index: usize) -> Option<&Component> {
-        self.0.get(&index)
+impl<T> StorageMut for MapStorage<T> {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.0.get_mut(&entity.index()) {
+            Some((generation, component)) if *generation == entity.generation() => Some(component),
+            _ => None,
+        }
     }
 }
 
-impl<'a> StorageMut<'a> for &'a mut MapStorage {
-    fn get_mut(&mut self, index: usize) -> Option<&mut Component> {
-        self.0.get_mut(&index)
+impl<T> StorageInsert for MapStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
+        self.0.insert(entity.index(), (entity.generation(), component));
+    }
+
+    fn remove(&mut self, entity: Entity) -> Option<T> {
+        match self.0.get(&entity.index()) {
+            Some((generation, _)) if *generation == entity.generation() => {
+                self.0.remove(&entity.index()).map(|(_, component)| component)
+            },
+            _ => None,
+        }
     }
 }
 
-impl<'a> IntoIterator for &'a mut MapStorage {
-    type Item = (usize, &'a mut Component);
-    type IntoIter = MapStorageIter<'a>;
+// SAFETY: the map holds one entry per entity index
+unsafe impl<T> DistinctStorage for MapStorage<T> {}
+
+impl<T: Component> AnyStorage for MapStorage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut MapStorage<T> {
+    type Item = (Entity, &'a mut T);
+    type IntoIter = MapStorageIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         MapStorageIter(self.0.iter_mut())
     }
 }
 
-pub struct MapStorageIter<'a>(IterMut<'a, usize, Component>);
+pub struct MapStorageIter<'a, T: 'a>(IterMut<'a, usize, (Generation, T)>);
 
-impl<'a> Iterator for MapStorageIter<'a> {
-    type Item = (usize, &'a mut Component);
+impl<'a, T> Iterator for MapStorageIter<'a, T> {
+    type Item = (Entity, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|(index, component)| (*index, component))
+        self.0.next().map(|(index, (generation, component))| (Entity::new(*index, *generation), component))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_entity_is_not_found() {
+        let mut map = MapStorage::new();
+        map.insert(Entity::new(0, 1), 1);
+
+        assert_eq!(map.get(Entity::new(0, 0)), None);
+        assert_eq!(map.get_mut(Entity::new(0, 0)), None);
+        assert_eq!(map.get(Entity::new(0, 1)), Some(&1));
+    }
+
+    #[test]
+    fn removing_deletes_key() {
+        let mut map = MapStorage::new();
+        map.insert(Entity::new(0, 1), 1);
+
+        assert_eq!(map.remove(Entity::new(0, 0)), None);
+        assert_eq!(map.remove(Entity::new(0, 1)), Some(1));
+        assert_eq!(map.get(Entity::new(0, 1)), None);
+        assert!(map.is_empty());
     }
 }
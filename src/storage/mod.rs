@@ -1,12 +1,342 @@
+pub use self::change::{ChangeTicks, Tick};
+
+use self::archetype::{ArchetypeId, ArchetypeStorage};
+
 use component::Component;
+use entity::Entity;
+
+use std::any::{self, Any};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
 
+pub mod archetype;
+pub mod change;
 pub mod map;
 pub mod sequence;
+pub mod sparse;
+
+/// Read access to the components of a single type
+pub trait Storage {
+    type Component;
+
+    fn get(&self, entity: Entity) -> Option<&Self::Component>;
+
+    /// The number of components held
+    fn len(&self) -> usize;
+
+    /// Every entity that has a component in this storage
+    fn entities(&self) -> Vec<Entity>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+}
+
+/// Write access to the components already in a storage
+pub trait StorageMut: Storage {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut Self::Component>;
+}
+
+/// A storage that components can be attached to and detached from directly
+///
+/// The storages borrowed from a `World` do not implement this, since attaching or detaching a
+/// component also updates the entity's signature and, in an archetype world, moves the entity
+/// between tables. Use `World::insert_component`, `World::remove_component` or a
+/// `CommandBuffer` for those.
+pub trait StorageInsert: StorageMut {
+    /// Attaches a component to an entity, replacing any it already had
+    fn insert(&mut self, entity: Entity, component: Self::Component);
+
+    fn remove(&mut self, entity: Entity) -> Option<Self::Component>;
+}
+
+/// A storage that lists every entity at most once and lends out disjoint components
+///
+/// Joins fetch a component for each entity listed by one storage while holding the components
+/// fetched before, so they only accept storages that promise this.
+///
+/// # Safety
+///
+/// `entities` must never return an entity twice, and if the storage is `StorageMut`, `get_mut`
+/// must return references that do not overlap for different entities.
+pub unsafe trait DistinctStorage: Storage {}
+
+/// A storage whose component type has been erased so it can live in the `World` registry
+pub trait AnyStorage: Any + Send + Sync {
+    /// Drops the entity's component, if it has one
+    fn remove_entity(&mut self, entity: Entity);
+
+    /// Moves the entity's component to the table of its new archetype, for storages that have them
+    fn relocate(&mut self, _entity: Entity, _archetype: ArchetypeId) {}
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct Slot {
+    storage: Box<dyn AnyStorage>,
+    ticks: ChangeTicks,
+}
+
+/// A registered storage and its change ticks, which can be borrowed from a shared `World`
+pub(crate) struct StorageCell(RwLock<Slot>);
+
+impl StorageCell {
+    pub(crate) fn new<T: Component>() -> Self {
+        StorageCell(RwLock::new(Slot { storage: Box::new(T::Storage::default()), ticks: ChangeTicks::new() }))
+    }
+
+    /// Creates a cell holding an archetype table storage rather than `T::Storage`
+    pub(crate) fn archetype<T: Component>() -> Self {
+        StorageCell(RwLock::new(Slot { storage: Box::new(ArchetypeStorage::<T>::new()), ticks: ChangeTicks::new() }))
+    }
+
+    /// Drops the entity's component and its change ticks, if it has one
+    pub(crate) fn remove_entity(&mut self, entity: Entity) {
+        let slot = self.slot_mut();
+        slot.storage.remove_entity(entity);
+        slot.ticks.clear(entity);
+    }
+
+    pub(crate) fn relocate(&mut self, entity: Entity, archetype: ArchetypeId) {
+        self.slot_mut().storage.relocate(entity, archetype);
+    }
+
+    fn slot_mut(&mut self) -> &mut Slot {
+        match self.0.get_mut() {
+            Ok(slot) => slot,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub(crate) fn read<T: Component>(&self) -> ReadStorage<'_, T> {
+        let guard = match self.0.try_read() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                panic!("`{}` storage is already mutably borrowed", any::type_name::<T>())
+            },
+        };
+
+        ReadStorage { guard, marker: PhantomData }
+    }
+
+    /// Borrows the storage for writing, recording changes at `tick`
+    pub(crate) fn write<T: Component>(&self, tick: Tick) -> WriteStorage<'_, T> {
+        let guard = match self.0.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                panic!("`{}` storage is already borrowed", any::type_name::<T>())
+            },
+        };
+
+        WriteStorage { guard, tick, marker: PhantomData }
+    }
+}
+
+/// Shared access to the storage of `T` borrowed from a `World`
+pub struct ReadStorage<'a, T: Component> {
+    guard: RwLockReadGuard<'a, Slot>,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T: Component> ReadStorage<'a, T> {
+    pub fn change_ticks(&self) -> &ChangeTicks {
+        &self.guard.ticks
+    }
+
+    /// The archetype tables `T` is kept in
+    ///
+    /// Panics if the world keeps `T` in its own storage instead.
+    pub(crate) fn archetype_storage(&self) -> &ArchetypeStorage<T> {
+        archetype_storage::<T>(self.guard.storage.as_any())
+    }
 
-pub trait Storage<'a> {
-    fn get(&self, index: usize) -> Option<&Component>;
+    fn backend(&self) -> &dyn StorageMut<Component = T> {
+        backend::<T>(self.guard.storage.as_any())
+    }
 }
 
-pub trait StorageMut<'a> {
-    fn get_mut(&mut self, index: usize) -> Option<&mut Component>;
+/// Derefs to the component's own storage type
+///
+/// Panics if the world keeps `T` in archetype tables instead.
+impl<'a, T: Component> Deref for ReadStorage<'a, T> {
+    type Target = T::Storage;
+
+    fn deref(&self) -> &Self::Target {
+        own_storage::<T>(self.guard.storage.as_any())
+    }
+}
+
+/// Exclusive access to the storage of `T` borrowed from a `World`
+///
+/// Inserting, and mutable access through `StorageMut` or a join, record the world's current
+/// tick as the component's change tick. Only shared access reaches the component's own storage
+/// type, so no mutation goes unrecorded.
+pub struct WriteStorage<'a, T: Component> {
+    guard: RwLockWriteGuard<'a, Slot>,
+    tick: Tick,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T: Component> WriteStorage<'a, T> {
+    pub fn change_ticks(&self) -> &ChangeTicks {
+        &self.guard.ticks
+    }
+
+    /// The tick recorded for changes made through this storage
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    /// Attaches the entity's component in a world that keeps `T` in its own storage
+    pub(crate) fn insert(&mut self, entity: Entity, component: T) {
+        let Slot { storage, ticks } = &mut *self.guard;
+        let storage: &mut T::Storage = storage.as_any_mut().downcast_mut().expect("storage is not the component's own");
+        storage.insert(entity, component);
+        ticks.mark_added(entity, self.tick);
+    }
+
+    /// Detaches the entity's component in a world that keeps `T` in its own storage
+    pub(crate) fn remove(&mut self, entity: Entity) -> Option<T> {
+        let Slot { storage, ticks } = &mut *self.guard;
+        let storage: &mut T::Storage = storage.as_any_mut().downcast_mut().expect("storage is not the component's own");
+        ticks.clear(entity);
+        storage.remove(entity)
+    }
+
+    /// Places the entity's component in the table of its archetype, in an archetype world
+    pub(crate) fn insert_into(&mut self, entity: Entity, archetype: ArchetypeId, component: T) {
+        let Slot { storage, ticks } = &mut *self.guard;
+        let storage: &mut ArchetypeStorage<T> = storage.as_any_mut().downcast_mut().expect("storage is not an archetype storage");
+        storage.insert_into(entity, archetype, component);
+        ticks.mark_added(entity, self.tick);
+    }
+
+    /// Removes the entity's component from its table, in an archetype world
+    pub(crate) fn take(&mut self, entity: Entity) -> Option<T> {
+        let Slot { storage, ticks } = &mut *self.guard;
+        let storage: &mut ArchetypeStorage<T> = storage.as_any_mut().downcast_mut().expect("storage is not an archetype storage");
+        ticks.clear(entity);
+        storage.take(entity)
+    }
+
+    pub(crate) fn archetype_storage(&self) -> &ArchetypeStorage<T> {
+        archetype_storage::<T>(self.guard.storage.as_any())
+    }
+
+    /// The archetype tables `T` is kept in, and the change ticks to record mutable access in
+    ///
+    /// Panics if the world keeps `T` in its own storage instead.
+    pub(crate) fn archetype_storage_mut(&mut self) -> (&mut ArchetypeStorage<T>, &mut ChangeTicks) {
+        let Slot { storage, ticks } = &mut *self.guard;
+        match storage.as_any_mut().downcast_mut() {
+            Some(storage) => (storage, ticks),
+            None => panic!("`{}` is kept in its own storage, not archetype tables", any::type_name::<T>()),
+        }
+    }
+
+    fn backend(&self) -> &dyn StorageMut<Component = T> {
+        backend::<T>(self.guard.storage.as_any())
+    }
+
+    fn backend_mut(&mut self) -> (&mut dyn StorageMut<Component = T>, &mut ChangeTicks) {
+        let Slot { storage, ticks } = &mut *self.guard;
+        let any = storage.as_any_mut();
+        let storage: &mut dyn StorageMut<Component = T> = if any.is::<T::Storage>() {
+            any.downcast_mut::<T::Storage>().unwrap()
+        } else {
+            any.downcast_mut::<ArchetypeStorage<T>>().expect("storage registered under the wrong type")
+        };
+
+        (storage, ticks)
+    }
+}
+
+/// Derefs to the component's own storage type
+///
+/// Panics if the world keeps `T` in archetype tables instead.
+impl<'a, T: Component> Deref for WriteStorage<'a, T> {
+    type Target = T::Storage;
+
+    fn deref(&self) -> &Self::Target {
+        own_storage::<T>(self.guard.storage.as_any())
+    }
+}
+
+fn own_storage<T: Component>(storage: &dyn Any) -> &T::Storage {
+    match storage.downcast_ref() {
+        Some(storage) => storage,
+        None => panic!("`{}` is kept in archetype tables, not its own storage", any::type_name::<T>()),
+    }
+}
+
+fn archetype_storage<T: Component>(storage: &dyn Any) -> &ArchetypeStorage<T> {
+    match storage.downcast_ref() {
+        Some(storage) => storage,
+        None => panic!("`{}` is kept in its own storage, not archetype tables", any::type_name::<T>()),
+    }
+}
+
+fn backend<T: Component>(storage: &dyn Any) -> &dyn StorageMut<Component = T> {
+    match storage.downcast_ref::<T::Storage>() {
+        Some(storage) => storage,
+        None => storage.downcast_ref::<ArchetypeStorage<T>>().expect("storage registered under the wrong type"),
+    }
+}
+
+impl<'a, T: Component> Storage for ReadStorage<'a, T> {
+    type Component = T;
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.backend().get(entity)
+    }
+
+    fn len(&self) -> usize {
+        self.backend().len()
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.backend().entities()
+    }
+}
+
+impl<'a, T: Component> Storage for WriteStorage<'a, T> {
+    type Component = T;
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.backend().get(entity)
+    }
+
+    fn len(&self) -> usize {
+        self.backend().len()
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.backend().entities()
+    }
+}
+
+// SAFETY: both guards hand everything on to `T::Storage`, which `Component` requires to be
+// distinct, or to an `ArchetypeStorage`
+unsafe impl<'a, T: Component> DistinctStorage for ReadStorage<'a, T> {}
+
+// SAFETY: as for `ReadStorage`
+unsafe impl<'a, T: Component> DistinctStorage for WriteStorage<'a, T> {}
+
+impl<'a, T: Component> StorageMut for WriteStorage<'a, T> {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        let tick = self.tick;
+        let (storage, ticks) = self.backend_mut();
+        let component = storage.get_mut(entity)?;
+        ticks.mark_changed(entity, tick);
+        Some(component)
+    }
 }
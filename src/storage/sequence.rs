@@ -1,73 +1,152 @@
-use super::{Storage, StorageMut};
+use super::{AnyStorage, DistinctStorage, Storage, StorageInsert, StorageMut};
 use component::Component;
+use entity::{Entity, Generation};
 
-use std::slice::IterMut;
+use std::any::Any;
+use std::iter::Zip;
+use std::slice::{Iter, IterMut};
 
-pub struct SequenceStorage(Vec<Component>);
+/// Stores components in a vector indexed by entity, best suited to components most entities have
+pub struct SequenceStorage<T> {
+    components: Vec<Option<T>>,
+    generations: Vec<Generation>,
+    len: usize,
+}
 
-impl SequenceStorage {
+impl<T> SequenceStorage<T> {
     pub fn new() -> Self {
-        SequenceStorage(Vec::new())
+        Self {
+            components: Vec::new(),
+            generations: Vec::new(),
+            len: 0,
+        }
     }
 
-    pub fn size(&self) -> usize {
-        self.0.len()
+    pub fn as_slice(&self) -> &[Option<T>] {
+        &self.components
     }
 
-    pub fn add(&mut self, index: usize, component: Component) {
-        ensure_index_fits(index, &mut self.0);
-        self.0.push(component)
+    fn slot(&self, entity: Entity) -> Option<usize> {
+        match self.generations.get(entity.index()) {
+            Some(generation) if *generation == entity.generation() => Some(entity.index()),
+            _ => None,
+        }
     }
+}
 
-    pub fn as_slice(&self) -> &[Component] {
-        &self.0
+impl<T> Default for SequenceStorage<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl<'a> Storage<'a> for &'a SequenceStorage {
-    fn get(&self, index: usize) -> Option<&Component> {
-        self.0.get(index)
+impl<T> Storage for SequenceStorage<T> {
+    type Component = T;
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.slot(entity).and_then(|index| self.components[index].as_ref())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.generations.iter().zip(&self.components).enumerate()
+            .filter(|(_, (_, component))| component.is_some())
+            .map(|(index, (generation, _))| Entity::new(index, *generation))
+            .collect()
     }
 }
 
-impl<'a> StorageMut<'a> for &'a mut SequenceStorage {
-    fn get_mut(&mut self, index: usize) -> Option<&mut Component> {
-        self.0.get_mut(index)
+impl<T> StorageMut for SequenceStorage<T> {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slot(entity) {
+            Some(index) => self.components[index].as_mut(),
+            None => None,
+        }
     }
 }
 
-impl<'a> IntoIterator for &'a mut SequenceStorage {
-    type Item = (usize, &'a mut Component);
-    type IntoIter = SequenceStorageIter<'a>;
+impl<T> StorageInsert for SequenceStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
+        let index = entity.index();
+        ensure_index_fits(index, &mut self.components, &mut self.generations);
+
+        if self.components[index].is_none() {
+            self.len += 1;
+        }
+
+        self.components[index] = Some(component);
+        self.generations[index] = entity.generation();
+    }
+
+    /// Empties the entity's slot, returning the component it held
+    fn remove(&mut self, entity: Entity) -> Option<T> {
+        let component = self.slot(entity).and_then(|index| self.components[index].take());
+        if component.is_some() {
+            self.len -= 1;
+        }
+
+        component
+    }
+}
+
+// SAFETY: each entity index has one slot, listed once
+unsafe impl<T> DistinctStorage for SequenceStorage<T> {}
+
+impl<T: Component> AnyStorage for SequenceStorage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SequenceStorage<T> {
+    type Item = (Entity, &'a mut T);
+    type IntoIter = SequenceStorageIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        SequenceStorageIter(0, self.0.iter_mut())
+        SequenceStorageIter(0, self.generations.iter().zip(self.components.iter_mut()))
     }
 }
 
-pub struct SequenceStorageIter<'a>(usize, IterMut<'a, Component>);
+pub struct SequenceStorageIter<'a, T: 'a>(usize, Zip<Iter<'a, Generation>, IterMut<'a, Option<T>>>);
 
-impl<'a> Iterator for SequenceStorageIter<'a> {
-    type Item = (usize, &'a mut Component);
+impl<'a, T> Iterator for SequenceStorageIter<'a, T> {
+    type Item = (Entity, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.1.next().map(|component| {
+        for (generation, component) in &mut self.1 {
             let index = self.0;
             self.0 += 1;
 
-            (index, component)
-        })
+            if let Some(component) = component {
+                return Some((Entity::new(index, *generation), component));
+            }
+        }
+
+        None
     }
 }
 
-fn ensure_index_fits(index: usize, components: &mut Vec<Component>) {
+fn ensure_index_fits<T>(index: usize, components: &mut Vec<Option<T>>, generations: &mut Vec<Generation>) {
     let allocated = components.len();
-    if index > allocated {
-        components.reserve(index - allocated);
+    if index >= allocated {
+        components.reserve(index + 1 - allocated);
+        generations.reserve(index + 1 - allocated);
     }
 
-    for _ in components.len()..index {
-        components.push(Component::Empty);
+    for _ in components.len()..=index {
+        components.push(None);
+        generations.push(0);
     }
 }
 
@@ -76,14 +155,40 @@ mod tests {
     use super::*;
 
     #[test]
-    fn iterator_yields_index_and_component() {
+    fn iterator_yields_entity_and_component_skipping_empty_slots() {
         let mut seq = SequenceStorage::new();
-        seq.add(0, Component::Empty);
-        seq.add(1, Component::Empty);
+        seq.insert(Entity::new(0, 0), 1);
+        seq.insert(Entity::new(2, 2), 3);
 
         let mut iter = seq.into_iter();
-        assert_eq!(iter.next(), Some((0, &mut Component::Empty)));
-        assert_eq!(iter.next(), Some((1, &mut Component::Empty)));
+        assert_eq!(iter.next(), Some((Entity::new(0, 0), &mut 1)));
+        assert_eq!(iter.next(), Some((Entity::new(2, 2), &mut 3)));
         assert_eq!(iter.next(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn stale_entity_is_not_found() {
+        let mut seq = SequenceStorage::new();
+        seq.insert(Entity::new(0, 0), 1);
+        seq.insert(Entity::new(0, 1), 2);
+
+        assert_eq!(seq.get(Entity::new(0, 0)), None);
+        assert_eq!(seq.get_mut(Entity::new(0, 0)), None);
+        assert_eq!(seq.get(Entity::new(0, 1)), Some(&2));
+        assert_eq!(seq.as_slice(), [Some(2)]);
+        assert_eq!(seq.len(), 1);
+    }
+
+    #[test]
+    fn removing_empties_slot() {
+        let mut seq = SequenceStorage::new();
+        seq.insert(Entity::new(0, 0), 1);
+        seq.insert(Entity::new(1, 0), 2);
+
+        assert_eq!(seq.remove(Entity::new(0, 1)), None);
+        assert_eq!(seq.remove(Entity::new(0, 0)), Some(1));
+        assert_eq!(seq.remove(Entity::new(0, 0)), None);
+        assert_eq!(seq.as_slice(), [None, Some(2)]);
+        assert_eq!(seq.len(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+use super::{AnyStorage, DistinctStorage, Storage, StorageInsert, StorageMut};
+use component::Component;
+use entity::Entity;
+
+use std::any::Any;
+use std::iter::Zip;
+use std::slice::{Iter, IterMut};
+
+/// Stores components packed in a dense array, found through a sparse index by entity
+///
+/// Inserting, removing and looking up are constant time without hashing, and iteration walks
+/// the dense array, so it is both fast and in a deterministic order. Removing moves the last
+/// component into the gap, so the order is that of insertion only until something is removed.
+pub struct SparseSetStorage<T> {
+    sparse: Vec<Option<usize>>,
+    entities: Vec<Entity>,
+    components: Vec<T>,
+}
+
+impl<T> SparseSetStorage<T> {
+    pub fn new() -> Self {
+        Self {
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    /// The components in iteration order
+    pub fn as_slice(&self) -> &[T] {
+        &self.components
+    }
+
+    fn slot(&self, entity: Entity) -> Option<usize> {
+        match self.sparse.get(entity.index()) {
+            Some(Some(dense)) if self.entities[*dense] == entity => Some(*dense),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Default for SparseSetStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Storage for SparseSetStorage<T> {
+    type Component = T;
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.slot(entity).map(|dense| &self.components[dense])
+    }
+
+    fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.entities.clone()
+    }
+}
+
+impl<T> StorageMut for SparseSetStorage<T> {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slot(entity) {
+            Some(dense) => Some(&mut self.components[dense]),
+            None => None,
+        }
+    }
+}
+
+impl<T> StorageInsert for SparseSetStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
+        let index = entity.index();
+        if self.sparse.len() <= index {
+            self.sparse.resize(index + 1, None);
+        }
+
+        match self.sparse[index] {
+            Some(dense) => {
+                self.entities[dense] = entity;
+                self.components[dense] = component;
+            },
+            None => {
+                self.sparse[index] = Some(self.components.len());
+                self.entities.push(entity);
+                self.components.push(component);
+            },
+        }
+    }
+
+    /// Removes the entity's component, moving the last component into its place
+    fn remove(&mut self, entity: Entity) -> Option<T> {
+        let dense = self.slot(entity)?;
+        self.sparse[entity.index()] = None;
+
+        self.entities.swap_remove(dense);
+        let component = self.components.swap_remove(dense);
+        if let Some(moved) = self.entities.get(dense) {
+            self.sparse[moved.index()] = Some(dense);
+        }
+
+        Some(component)
+    }
+}
+
+// SAFETY: the sparse index points each entity index at its own dense slot
+unsafe impl<T> DistinctStorage for SparseSetStorage<T> {}
+
+impl<T: Component> AnyStorage for SparseSetStorage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SparseSetStorage<T> {
+    type Item = (Entity, &'a mut T);
+    type IntoIter = SparseSetStorageIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SparseSetStorageIter(self.entities.iter().zip(self.components.iter_mut()))
+    }
+}
+
+pub struct SparseSetStorageIter<'a, T: 'a>(Zip<Iter<'a, Entity>, IterMut<'a, T>>);
+
+impl<'a, T> Iterator for SparseSetStorageIter<'a, T> {
+    type Item = (Entity, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(entity, component)| (*entity, component))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_entity_is_not_found() {
+        let mut set = SparseSetStorage::new();
+        set.insert(Entity::new(0, 0), 1);
+        set.insert(Entity::new(0, 1), 2);
+
+        assert_eq!(set.get(Entity::new(0, 0)), None);
+        assert_eq!(set.get_mut(Entity::new(0, 0)), None);
+        assert_eq!(set.remove(Entity::new(0, 0)), None);
+        assert_eq!(set.get(Entity::new(0, 1)), Some(&2));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn removing_keeps_components_packed() {
+        let mut set = SparseSetStorage::new();
+        for index in 0..4 {
+            set.insert(Entity::new(index * 3, 0), index);
+        }
+
+        assert_eq!(set.remove(Entity::new(3, 0)), Some(1));
+        assert_eq!(set.as_slice(), [0, 3, 2]);
+        assert_eq!(set.entities(), vec![Entity::new(0, 0), Entity::new(9, 0), Entity::new(6, 0)]);
+        assert_eq!(set.get(Entity::new(9, 0)), Some(&3));
+
+        let mut iter = set.into_iter();
+        assert_eq!(iter.next(), Some((Entity::new(0, 0), &mut 0)));
+        assert_eq!(iter.next(), Some((Entity::new(9, 0), &mut 3)));
+    }
+}
@@ -0,0 +1,117 @@
+use std::any::{self, TypeId};
+use std::fmt;
+
+/// Identifies a component storage or resource a system borrows from the `World`
+#[derive(Clone, Copy)]
+pub struct StorageId {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl StorageId {
+    pub fn of<T: 'static>() -> Self {
+        Self { id: TypeId::of::<T>(), name: any::type_name::<T>() }
+    }
+
+    /// The name of the stored type, for diagnostics
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl PartialEq for StorageId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for StorageId {}
+
+impl fmt::Debug for StorageId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// The storages and resources a system reads and writes when it runs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Access {
+    reads: Vec<StorageId>,
+    writes: Vec<StorageId>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read<T: 'static>(mut self) -> Self {
+        let id = StorageId::of::<T>();
+        if !self.reads.contains(&id) {
+            self.reads.push(id);
+        }
+
+        self
+    }
+
+    pub fn write<T: 'static>(mut self) -> Self {
+        let id = StorageId::of::<T>();
+        if !self.writes.contains(&id) {
+            self.writes.push(id);
+        }
+
+        self
+    }
+
+    pub fn reads(&self) -> &[StorageId] {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &[StorageId] {
+        &self.writes
+    }
+
+    /// The first storage or resource both accesses write to
+    pub fn conflicting_write(&self, other: &Access) -> Option<StorageId> {
+        self.writes.iter().find(|id| other.writes.contains(id)).cloned()
+    }
+
+    /// Whether running alongside `other` could borrow the same data both mutably and immutably
+    pub fn conflicts_with(&self, other: &Access) -> bool {
+        self.writes.iter().any(|id| other.writes.contains(id) || other.reads.contains(id))
+            || other.writes.iter().any(|id| self.reads.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readers_do_not_conflict() {
+        let first = Access::new().read::<u8>();
+        let second = Access::new().read::<u8>().write::<u16>();
+
+        assert!(!first.conflicts_with(&second));
+        assert_eq!(first.conflicting_write(&second), None);
+    }
+
+    #[test]
+    fn reader_and_writer_conflict() {
+        let first = Access::new().read::<u8>();
+        let second = Access::new().write::<u8>();
+
+        assert!(first.conflicts_with(&second));
+        assert!(second.conflicts_with(&first));
+        assert_eq!(first.conflicting_write(&second), None);
+    }
+
+    #[test]
+    fn writers_conflict() {
+        let first = Access::new().write::<u8>().write::<u16>();
+        let second = Access::new().write::<u16>();
+
+        assert!(first.conflicts_with(&second));
+        assert_eq!(first.conflicting_write(&second), Some(StorageId::of::<u16>()));
+    }
+}
@@ -1,40 +1,72 @@
-use component::Component::{self, KeysPressed, Commands};
-use command::Command;
-use input::Keys;
-use system::System;
-
-use std::time::Duration;
+use action::{ActionMap, Axes};
+use command::{Command, Commands, QuitRequested};
+use event::{EventWriter, Events};
+use input::InputState;
+use storage::Tick;
+use system::{Access, System};
+use time::Time;
+use world::World;
 
+/// Translates held keys into commands and axis values through the action map
+///
+/// Also requests that the app quits while the quit command is issued.
 pub struct CommandSystem;
 
-impl System for CommandSystem {
-    fn update(&self, dependent: &mut Component, independent: &Component, _: &Duration) {
-        if let (Commands(commands), KeysPressed(keys)) = (dependent, independent) {
-            if keys.is_set(Keys::Escape) {
-                commands.set(Command::Quit)
-            }
+impl CommandSystem {
+    pub fn update(&self, commands: &mut Commands, axes: &mut Axes, actions: &ActionMap, input: &InputState, _: &Time) {
+        let (issued, values) = actions.evaluate(input);
+        *commands = issued;
+        *axes = values;
+    }
+
+    pub fn request_quit(&self, commands: &Commands, quit: &mut EventWriter<QuitRequested>) {
+        if commands.0.is_set(Command::Quit) {
+            quit.send(QuitRequested);
         }
     }
 }
 
+impl System for CommandSystem {
+    fn access(&self) -> Access {
+        Access::new()
+            .write::<Commands>()
+            .write::<Axes>()
+            .write::<Events<QuitRequested>>()
+            .read::<ActionMap>()
+            .read::<InputState>()
+    }
+
+    fn run(&mut self, world: &World, time: &Time, _: Tick) {
+        self.update(
+            &mut world.resource_mut::<Commands>(),
+            &mut world.resource_mut::<Axes>(),
+            &world.resource::<ActionMap>(),
+            &world.resource::<InputState>(),
+            time,
+        );
+
+        self.request_quit(&world.resource::<Commands>(), &mut world.event_writer::<QuitRequested>());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use action::Axis;
+    use event::EventReader;
+    use input::Keys;
 
     #[test]
-    fn no_keys_results_in_unmodified_commands() {
-        let mut commands = Commands(0.into());
-        let keys = KeysPressed(0.into());
+    fn no_keys_results_in_no_commands() {
+        let mut commands = Commands(0b10.into());
+        let mut axes = Axes::new();
+        axes.set(Axis::MoveX, 1.0);
 
         let system = CommandSystem;
-        let duration = Duration::new(0, 0);
+        system.update(&mut commands, &mut axes, &ActionMap::default(), &InputState::new(), &Time::new());
 
-        system.update(&mut commands, &keys, &duration);
         assert_eq!(commands, Commands(0.into()));
-
-        commands = Commands(0b10.into());
-        system.update(&mut commands, &keys, &duration);
-        assert_eq!(commands, Commands(0b10.into()));
+        assert_eq!(axes, Axes::new());
     }
 
     #[test]
@@ -42,16 +74,36 @@ mod tests {
         use util::BitVector;
 
         let mut commands = Commands(0.into());
-        let mut keys_bits: BitVector = 0.into();
-        keys_bits.set(Keys::Escape);
-        let keys = KeysPressed(keys_bits);
+        let mut axes = Axes::new();
+        let mut input = InputState::new();
+        input.press(Keys::Escape);
 
         let system = CommandSystem;
-        let duration = Duration::new(0, 0);
+        system.update(&mut commands, &mut axes, &ActionMap::default(), &input, &Time::new());
 
-        system.update(&mut commands, &keys, &duration);
         let mut commands_bits: BitVector = 0.into();
         commands_bits.set(Command::Quit);
         assert_eq!(commands, Commands(commands_bits));
     }
+
+    #[test]
+    fn run_updates_world_resources() {
+        use world::World;
+
+        let mut world = World::new();
+        world.insert_resource(ActionMap::default());
+        world.insert_resource(Axes::default());
+        world.insert_resource(Commands::default());
+        world.insert_resource(InputState::new());
+        world.add_event::<QuitRequested>();
+        world.resource_mut::<InputState>().press(Keys::Escape);
+        world.resource_mut::<InputState>().press(Keys::A);
+
+        CommandSystem.run(&world, &Time::new(), 0);
+        assert!(world.resource::<Commands>().0.is_set(Command::Quit));
+        assert_eq!(world.resource::<Axes>().get(Axis::MoveX), -1.0);
+
+        let mut quit = EventReader::<QuitRequested>::new();
+        assert_eq!(quit.read(&world.resource::<Events<QuitRequested>>()).count(), 1);
+    }
 }
@@ -1,20 +1,30 @@
-use component::Component::{self, KeysPressed, Velocity};
-use input::Keys;
-use system::System;
-
-use std::time::Duration;
+use action::{Axes, Axis};
+use component::Velocity;
+use storage::{StorageMut, Tick};
+use system::{Access, System};
+use time::Time;
+use world::World;
 
+/// Steers the player with the movement axes
 pub struct KeysSystem;
 
+impl KeysSystem {
+    pub fn update(&self, velocity: &mut Velocity, axes: &Axes, _: &Time) {
+        velocity.0 += axes.get(Axis::MoveX);
+        velocity.1 += axes.get(Axis::MoveY);
+    }
+}
+
 impl System for KeysSystem {
-    fn update(&self, dependent: &mut Component, independent: &Component, _: &Duration) {
-        if let (Velocity(x, y), KeysPressed(state)) = (dependent, independent) {
-            let process = |key, func: &mut FnMut()| if state.is_set(key) { func() };
-
-            process(Keys::W, &mut || *y += 1.0);
-            process(Keys::S, &mut || *y -= 1.0);
-            process(Keys::A, &mut || *x -= 1.0);
-            process(Keys::D, &mut || *x += 1.0);
+    fn access(&self) -> Access {
+        Access::new().write::<Velocity>().read::<Axes>()
+    }
+
+    fn run(&mut self, world: &World, time: &Time, _: Tick) {
+        if let Some(player_id) = world.player_id() {
+            if let Some(velocity) = world.write::<Velocity>().get_mut(player_id) {
+                self.update(velocity, &world.resource::<Axes>(), time);
+            }
         }
     }
 }
@@ -24,30 +34,50 @@ mod tests {
     use super::*;
 
     #[test]
-    fn no_keys_pressed() {
+    fn no_axes_moved() {
         let mut velocity = Velocity(1.0, 0.0);
-        let delta = Duration::new(0, 0);
-        KeysSystem.update(&mut velocity, &KeysPressed(0.into()), &delta);
+        let time = Time::new();
+        KeysSystem.update(&mut velocity, &Axes::new(), &time);
 
         assert_eq!(velocity, Velocity(1.0, 0.0));
     }
 
     #[test]
-    fn one_movement_key_pressed() {
+    fn one_axis_moved() {
         let mut velocity = Velocity(1.0, 0.0);
-        let delta = Duration::new(0, 0);
-        KeysSystem.update(&mut velocity, &KeysPressed((1_u128 << Keys::W as u64).into()), &delta);
+        let mut axes = Axes::new();
+        axes.set(Axis::MoveY, 1.0);
+        KeysSystem.update(&mut velocity, &axes, &Time::new());
 
         assert_eq!(velocity, Velocity(1.0, 1.0));
     }
 
     #[test]
-    fn multiple_movement_keys_pressed() {
+    fn both_axes_moved() {
         let mut velocity = Velocity(1.0, 0.0);
-        let delta = Duration::new(0, 0);
-        let keys_state = KeysPressed((1_u128 << Keys::W as u64 | 1_u128 << Keys::D as u64).into());
-        KeysSystem.update(&mut velocity, &keys_state.into(), &delta);
+        let mut axes = Axes::new();
+        axes.set(Axis::MoveX, 1.0);
+        axes.set(Axis::MoveY, 1.0);
+        KeysSystem.update(&mut velocity, &axes, &Time::new());
 
         assert_eq!(velocity, Velocity(2.0, 1.0));
     }
+
+    #[test]
+    fn only_player_velocity_is_updated() {
+        use world::World;
+
+        let mut world = World::new();
+        world.register::<Velocity>();
+        world.insert_resource(Axes::default());
+        let other = world.create_entity().with_component(Velocity(0.0, 0.0)).build();
+        let player = world.create_entity().with_component(Velocity(0.0, 0.0)).make_player().build();
+        world.resource_mut::<Axes>().set(Axis::MoveX, -1.0);
+
+        KeysSystem.run(&world, &Time::new(), 0);
+
+        let velocities = world.read::<Velocity>();
+        assert_eq!(velocities.as_slice()[other.index()], Some(Velocity(0.0, 0.0)));
+        assert_eq!(velocities.as_slice()[player.index()], Some(Velocity(-1.0, 0.0)));
+    }
 }
@@ -1,97 +1,95 @@
-use component::Component;
-use storage::{Storage, StorageMut};
-
-use std::time::Duration;
+use storage::Tick;
+use time::Time;
+use world::World;
 
+pub mod access;
 pub mod command;
 pub mod keys;
 pub mod movement;
-
-pub trait System {
-    fn update(&self, dependent: &mut Component, independent: &Component, delta: &Duration);
-
-    fn run<'a, A, B>(&self, dependents: A, independents: B, delta: &Duration)
-    where
-        A: StorageMut<'a> + IntoIterator<Item = (usize, &'a mut Component)>,
-        B: Storage<'a>,
-    {
-        for (index, mut dependent) in dependents.into_iter().filter(not_empty) {
-            if let Some(independent) = independents.get(index).and_then(exists) {
-                self.update(&mut dependent, independent, delta);
-            }
-        }
-    }
-}
-
-fn not_empty((_, component): &(usize, &mut Component)) -> bool {
-    **component != Component::Empty
-}
-
-fn exists(component: &Component) -> Option<&Component> {
-    if *component != Component::Empty { Some(component) } else { None }
+mod pool;
+pub mod schedule;
+pub mod transform;
+
+pub use self::access::Access;
+pub use self::schedule::{Constraints, Executor, Schedule, ScheduleBuilder, ScheduleError};
+
+/// Logic that updates a world once per frame
+pub trait System: Send {
+    /// The storages and resources this system borrows in `run`
+    fn access(&self) -> Access;
+
+    /// Runs the system, given the world's change tick when it last ran, or 0 before its first run
+    ///
+    /// Passing `last_run` to `Added` or `Changed` visits only what changed since.
+    fn run(&mut self, world: &World, time: &Time, last_run: Tick);
+
+    /// Applies changes deferred during `run`, such as those in a `CommandBuffer`
+    ///
+    /// The schedule calls this once the system's stage has finished, when nothing else can be
+    /// borrowing from the world.
+    fn apply(&mut self, _world: &mut World) {}
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use component::Component::{Empty, Position, Velocity};
-    use storage::sequence::SequenceStorage;
-
-    use std::time::Duration;
+    use component::{Position, Velocity};
+    use query::Join;
 
     struct StubSystem;
 
     impl System for StubSystem {
-        fn update(&self, dependent: &mut Component, independent: &Component, _: &Duration) {
-            if let (Position(pos_x, pos_y), Velocity(vel_x, vel_y)) = (dependent, independent) {
-                *pos_x += vel_x;
-                *pos_y += vel_y;
+        fn access(&self) -> Access {
+            Access::new().write::<Position>().read::<Velocity>()
+        }
+
+        fn run(&mut self, world: &World, _: &Time, _: Tick) {
+            for (_, (position, velocity)) in (&mut world.write::<Position>(), &world.read::<Velocity>()).join() {
+                position.0 += velocity.0;
+                position.1 += velocity.1;
             }
         }
     }
 
     #[test]
-    fn dependent_is_empty() {
-        let mut positions = SequenceStorage::new();
-        let mut velocities = SequenceStorage::new();
-
-        positions.add(0, Empty);
-        velocities.add(0, Velocity(2.0, 2.0));
+    fn dependent_is_missing() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        world.create_entity().with_component(Velocity(2.0, 2.0)).build();
 
-        let delta = Duration::new(0, 0);
-        StubSystem.run(&mut positions, &velocities, &delta);
+        StubSystem.run(&world, &Time::new(), 0);
 
-        assert_eq!(positions.as_slice(), [Empty]);
-        assert_eq!(velocities.as_slice(), [Velocity(2.0, 2.0)]);
+        assert_eq!(world.read::<Position>().as_slice(), []);
+        assert_eq!(world.read::<Velocity>().as_slice(), [Some(Velocity(2.0, 2.0))]);
     }
 
     #[test]
-    fn independent_is_empty() {
-        let mut positions = SequenceStorage::new();
-        let mut velocities = SequenceStorage::new();
+    fn independent_is_missing() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        world.create_entity().with_component(Position(1.0, 1.0)).build();
 
-        positions.add(0, Position(1.0, 1.0));
-        velocities.add(0, Empty);
+        StubSystem.run(&world, &Time::new(), 0);
 
-        let delta = Duration::new(0, 0);
-        StubSystem.run(&mut positions, &velocities, &delta);
-
-        assert_eq!(positions.as_slice(), [Position(1.0, 1.0)]);
-        assert_eq!(velocities.as_slice(), [Empty]);
+        assert_eq!(world.read::<Position>().as_slice(), [Some(Position(1.0, 1.0))]);
+        assert_eq!(world.read::<Velocity>().as_slice(), []);
     }
 
     #[test]
     fn dependent_is_aligned_with_independent() {
-        let mut positions = SequenceStorage::new();
-        let mut velocities = SequenceStorage::new();
-
-        positions.add(0, Position(1.0, 1.0));
-        velocities.add(0, Velocity(2.0, 2.0));
-
-        let delta = Duration::new(0, 0);
-        StubSystem.run(&mut positions, &velocities, &delta);
-
-        assert_eq!(positions.as_slice(), [Position(3.0, 3.0)]);
-        assert_eq!(velocities.as_slice(), [Velocity(2.0, 2.0)]);
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        world.create_entity()
+            .with_component(Position(1.0, 1.0))
+            .with_component(Velocity(2.0, 2.0))
+            .build();
+
+        StubSystem.run(&world, &Time::new(), 0);
+
+        assert_eq!(world.read::<Position>().as_slice(), [Some(Position(3.0, 3.0))]);
+        assert_eq!(world.read::<Velocity>().as_slice(), [Some(Velocity(2.0, 2.0))]);
     }
 }
@@ -1,15 +1,28 @@
-use component::Component::{self, Position, Velocity};
-use system::System;
-
-use std::time::Duration;
+use component::{Position, Velocity};
+use query::Join;
+use storage::Tick;
+use system::{Access, System};
+use time::Time;
+use world::World;
 
 pub struct MovementSystem;
 
+impl MovementSystem {
+    pub fn update(&self, position: &mut Position, velocity: &Velocity, time: &Time) {
+        let Velocity(vel_x, vel_y) = *velocity;
+        position.0 += vel_x * time.delta_seconds_f64();
+        position.1 += vel_y * time.delta_seconds_f64();
+    }
+}
+
 impl System for MovementSystem {
-    fn update(&self, dependent: &mut Component, independent: &Component, delta: &Duration) {
-        if let (Position(pos_x, pos_y), Velocity(vel_x, vel_y)) = (dependent, independent) {
-            *pos_x += vel_x * (delta.as_secs() as f64);
-            *pos_y += vel_y * (delta.as_secs() as f64);
+    fn access(&self) -> Access {
+        Access::new().write::<Position>().read::<Velocity>()
+    }
+
+    fn run(&mut self, world: &World, time: &Time, _: Tick) {
+        for (_, (position, velocity)) in (&mut world.write::<Position>(), &world.read::<Velocity>()).join() {
+            self.update(position, velocity, time);
         }
     }
 }
@@ -18,15 +31,43 @@ impl System for MovementSystem {
 mod tests {
     use super::*;
 
+    use std::time::Duration;
+
     #[test]
     fn position_is_updated() {
         let mut pos = Position(2.0, 2.0);
         let vel = Velocity(1.0, 1.0);
 
-        let duration = Duration::new(3, 0);
+        let mut time = Time::new();
+        time.advance(Duration::new(3, 0));
         let system = MovementSystem;
-        system.update(&mut pos, &vel, &duration);
+        system.update(&mut pos, &vel, &time);
 
         assert_eq!(pos, Position(5.0, 5.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn position_is_updated_by_sub_second_frames() {
+        let mut pos = Position(0.0, 0.0);
+        let vel = Velocity(10.0, -5.0);
+
+        let mut time = Time::new();
+        time.advance(Duration::from_millis(250));
+        MovementSystem.update(&mut pos, &vel, &time);
+
+        assert_eq!(pos, Position(2.5, -1.25));
+    }
+
+    #[test]
+    fn paused_time_stops_movement() {
+        let mut pos = Position(1.0, 1.0);
+        let vel = Velocity(1.0, 1.0);
+
+        let mut time = Time::new();
+        time.set_time_scale(0.0);
+        time.advance(Duration::from_millis(16));
+        MovementSystem.update(&mut pos, &vel, &time);
+
+        assert_eq!(pos, Position(1.0, 1.0));
+    }
+}
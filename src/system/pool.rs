@@ -0,0 +1,111 @@
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Worker threads that live as long as the pool, running jobs that borrow from the caller
+pub(crate) struct ThreadPool {
+    workers: Vec<(Sender<Job>, JoinHandle<()>)>,
+    finished: Receiver<thread::Result<()>>,
+}
+
+impl ThreadPool {
+    pub(crate) fn new(threads: usize) -> Self {
+        let (finished_sender, finished) = mpsc::channel();
+        let workers = (0..threads)
+            .map(|index| {
+                let (sender, jobs) = mpsc::channel::<Job>();
+                let finished = finished_sender.clone();
+                let handle = thread::Builder::new()
+                    .name(format!("hikari-worker-{}", index))
+                    .spawn(move || {
+                        for job in jobs {
+                            if finished.send(panic::catch_unwind(AssertUnwindSafe(job))).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("failed to start a worker thread");
+
+                (sender, handle)
+            })
+            .collect();
+
+        Self { workers, finished }
+    }
+
+    pub(crate) fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Runs each job on its own worker and `local` on the calling thread, returning once all
+    /// of them have finished
+    ///
+    /// Panics if there are more jobs than workers, or, once everything has finished, if any of
+    /// them panicked.
+    pub(crate) fn scope<'a, F: FnOnce()>(&self, local: F, jobs: Vec<Box<dyn FnOnce() + Send + 'a>>) {
+        assert!(jobs.len() <= self.workers.len(), "more jobs than worker threads");
+
+        let mut sent = 0;
+        for (job, (sender, _)) in jobs.into_iter().zip(&self.workers) {
+            // SAFETY: this function does not return or unwind until every sent job has reported
+            // back, so nothing the jobs borrow is dropped while they can still use it
+            let job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'a>, Job>(job) };
+            if sender.send(job).is_ok() {
+                sent += 1;
+            }
+        }
+
+        let mut result = panic::catch_unwind(AssertUnwindSafe(local));
+        for _ in 0..sent {
+            let finished = self.finished.recv().expect("worker threads never stop while the pool is alive");
+            result = result.and(finished);
+        }
+
+        if let Err(payload) = result {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        for (sender, handle) in self.workers.drain(..) {
+            drop(sender);
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    #[test]
+    fn jobs_borrow_from_the_caller_and_reuse_workers() {
+        let pool = ThreadPool::new(2);
+        let threads = Mutex::new(Vec::new());
+
+        for _ in 0..5 {
+            let record = || threads.lock().unwrap().push(thread::current().id());
+            pool.scope(record, vec![Box::new(record), Box::new(record)]);
+        }
+
+        let mut threads = threads.into_inner().unwrap();
+        assert_eq!(threads.len(), 15);
+        threads.sort_by_key(|id| format!("{:?}", id));
+        threads.dedup();
+        assert_eq!(threads.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "job failed")]
+    fn panics_reach_the_caller_after_every_job_finishes() {
+        let pool = ThreadPool::new(1);
+        pool.scope(|| (), vec![Box::new(|| panic!("job failed"))]);
+    }
+}
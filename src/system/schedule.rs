@@ -0,0 +1,586 @@
+use super::System;
+use super::access::Access;
+use super::pool::ThreadPool;
+use storage::Tick;
+use time::Time;
+use world::World;
+
+use std::error::Error;
+use std::fmt;
+
+/// How a `Schedule` runs its systems
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Executor {
+    /// Runs every system on the calling thread, one after another
+    Serial,
+
+    /// Runs the systems of each stage concurrently across up to this many threads
+    ///
+    /// The schedule keeps all but the calling thread alive between runs.
+    Parallel { threads: usize },
+}
+
+/// Systems in the order they run each frame
+///
+/// Systems are grouped into stages. No two systems in a stage conflict over a storage, so a
+/// parallel executor can run them at once, and every stage only starts once the last has finished.
+pub struct Schedule {
+    systems: Vec<Entry>,
+    stages: Vec<usize>,
+    pool: Option<ThreadPool>,
+}
+
+impl Schedule {
+    pub fn builder() -> ScheduleBuilder {
+        ScheduleBuilder::new()
+    }
+
+    /// Switches executor, starting or stopping worker threads as needed
+    pub fn set_executor(&mut self, executor: Executor) {
+        let workers = match executor {
+            Executor::Serial => 0,
+            Executor::Parallel { threads } => threads.max(1) - 1,
+        };
+
+        if self.pool.as_ref().map_or(0, ThreadPool::threads) != workers {
+            self.pool = if workers == 0 { None } else { Some(ThreadPool::new(workers)) };
+        }
+    }
+
+    /// Runs every system once, stage by stage
+    ///
+    /// Both executors produce the same results, the serial one being deterministic to debug.
+    /// After each stage, its systems apply their deferred changes in order. The world's change
+    /// tick advances before each stage and before its changes are applied, and each system is
+    /// given the tick its stage last ran at, so it sees every change made since but its own.
+    pub fn run(&mut self, world: &mut World, time: &Time) {
+        let mut remaining = &mut self.systems[..];
+        for &len in &self.stages {
+            let (stage, rest) = remaining.split_at_mut(len);
+            remaining = rest;
+            let tick = world.advance_change_tick();
+            run_stage(stage, world, time, self.pool.as_ref());
+
+            world.advance_change_tick();
+            for entry in stage {
+                entry.last_run = tick;
+                entry.system.apply(world);
+            }
+        }
+    }
+
+    /// The names of the systems in the order they run
+    pub fn order(&self) -> Vec<&str> {
+        self.systems.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    /// The names of the systems in each stage
+    pub fn stages(&self) -> Vec<Vec<&str>> {
+        let mut names = self.order().into_iter();
+        self.stages.iter().map(|&len| names.by_ref().take(len).collect()).collect()
+    }
+}
+
+/// Splits a stage between the calling thread and the pool's workers, if there are any
+fn run_stage(stage: &mut [Entry], world: &World, time: &Time, pool: Option<&ThreadPool>) {
+    let pool = match pool {
+        Some(pool) if stage.len() > 1 => pool,
+        _ => return run_serial(stage, world, time),
+    };
+
+    let chunk_size = stage.len().div_ceil(pool.threads() + 1);
+    let mut chunks = stage.chunks_mut(chunk_size);
+    let local = chunks.next().unwrap();
+    let jobs = chunks
+        .map(|chunk| Box::new(move || run_serial(chunk, world, time)) as Box<dyn FnOnce() + Send>)
+        .collect();
+
+    pool.scope(|| run_serial(local, world, time), jobs);
+}
+
+fn run_serial(systems: &mut [Entry], world: &World, time: &Time) {
+    for entry in systems {
+        entry.system.run(world, time, entry.last_run);
+    }
+}
+
+struct Entry {
+    name: String,
+    system: Box<dyn System>,
+    access: Access,
+    before: Vec<String>,
+    after: Vec<String>,
+    last_run: Tick,
+}
+
+/// Collects systems and their ordering constraints before validating them into a `Schedule`
+#[derive(Default)]
+pub struct ScheduleBuilder {
+    systems: Vec<Entry>,
+}
+
+impl ScheduleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a system under a unique name
+    ///
+    /// Systems without ordering constraints between them run in the order they were added.
+    pub fn add<S: System + 'static>(&mut self, name: &str, system: S) -> Constraints<'_> {
+        self.systems.push(Entry {
+            name: name.to_owned(),
+            access: system.access(),
+            system: Box::new(system),
+            before: Vec::new(),
+            after: Vec::new(),
+            last_run: 0,
+        });
+
+        Constraints(self.systems.last_mut().unwrap())
+    }
+
+    /// Orders the systems into stages, checking for unknown names, cycles and unordered writers
+    pub fn build(self) -> Result<Schedule, ScheduleError> {
+        let (order, predecessors) = self.sort()?;
+
+        // Each system goes in the first stage after everything it must follow or conflicts with
+        let mut stage_of = vec![0; order.len()];
+        for (position, &index) in order.iter().enumerate() {
+            let access = &self.systems[index].access;
+            let conflicts = order[..position].iter()
+                .filter(|&&earlier| self.systems[earlier].access.conflicts_with(access));
+
+            stage_of[index] = predecessors[index].iter().chain(conflicts)
+                .map(|&earlier| stage_of[earlier] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+
+        let mut staged: Vec<(usize, usize)> = order.iter().enumerate()
+            .map(|(position, &index)| (stage_of[index], position))
+            .collect();
+        staged.sort();
+
+        let stage_count = stage_of.iter().max().map_or(0, |last| last + 1);
+        let mut stages = vec![0; stage_count];
+        for &(stage, _) in &staged {
+            stages[stage] += 1;
+        }
+
+        let mut slots: Vec<Option<Entry>> = self.systems.into_iter().map(Some).collect();
+        let systems = staged.into_iter().map(|(_, position)| slots[order[position]].take().unwrap()).collect();
+
+        Ok(Schedule { systems, stages, pool: None })
+    }
+
+    fn sort(&self) -> Result<(Vec<usize>, Vec<Vec<usize>>), ScheduleError> {
+        let mut predecessors = vec![Vec::new(); self.systems.len()];
+
+        for (index, entry) in self.systems.iter().enumerate() {
+            if self.systems[..index].iter().any(|other| other.name == entry.name) {
+                return Err(ScheduleError::DuplicateName(entry.name.clone()));
+            }
+
+            for name in &entry.after {
+                predecessors[index].push(self.position(&entry.name, name)?);
+            }
+
+            for name in &entry.before {
+                let successor = self.position(&entry.name, name)?;
+                predecessors[successor].push(index);
+            }
+        }
+
+        let order = topological_order(&predecessors).map_err(|cycle| {
+            ScheduleError::Cycle(cycle.iter().map(|&index| self.systems[index].name.clone()).collect())
+        })?;
+
+        let ancestors = ancestors(&order, &predecessors);
+        for (first, earlier) in self.systems.iter().enumerate() {
+            for (second, later) in self.systems.iter().enumerate().skip(first + 1) {
+                if ancestors[first][second] || ancestors[second][first] {
+                    continue;
+                }
+
+                if let Some(storage) = earlier.access.conflicting_write(&later.access) {
+                    return Err(ScheduleError::ConflictingWriters {
+                        first: earlier.name.clone(),
+                        second: later.name.clone(),
+                        storage: storage.name(),
+                    });
+                }
+            }
+        }
+
+        Ok((order, predecessors))
+    }
+
+    fn position(&self, system: &str, name: &str) -> Result<usize, ScheduleError> {
+        self.systems.iter().position(|entry| entry.name == name).ok_or_else(|| {
+            ScheduleError::UnknownSystem { system: system.to_owned(), dependency: name.to_owned() }
+        })
+    }
+}
+
+/// Ordering constraints for a system that was just added to a `ScheduleBuilder`
+pub struct Constraints<'a>(&'a mut Entry);
+
+impl<'a> Constraints<'a> {
+    /// Runs this system before the named one
+    pub fn before(self, name: &str) -> Self {
+        self.0.before.push(name.to_owned());
+        self
+    }
+
+    /// Runs this system after the named one
+    pub fn after(self, name: &str) -> Self {
+        self.0.after.push(name.to_owned());
+        self
+    }
+}
+
+/// Reasons a set of systems cannot be scheduled
+#[derive(Debug, PartialEq)]
+pub enum ScheduleError {
+    /// Two systems were registered under the same name
+    DuplicateName(String),
+
+    /// A system was ordered relative to a name that was never registered
+    UnknownSystem { system: String, dependency: String },
+
+    /// The ordering constraints form a loop through these systems
+    Cycle(Vec<String>),
+
+    /// Two systems write the same storage but nothing orders one before the other
+    ConflictingWriters { first: String, second: String, storage: &'static str },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScheduleError::DuplicateName(name) => write!(f, "system `{}` is registered twice", name),
+            ScheduleError::UnknownSystem { system, dependency } => {
+                write!(f, "system `{}` is ordered relative to unknown system `{}`", system, dependency)
+            },
+            ScheduleError::Cycle(names) => write!(f, "systems form an ordering cycle: {}", names.join(" -> ")),
+            ScheduleError::ConflictingWriters { first, second, storage } => {
+                write!(f, "systems `{}` and `{}` both write `{}` but are not ordered", first, second, storage)
+            },
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+/// Sorts nodes so each comes after its predecessors, preferring lower indices when free to choose
+///
+/// On failure, returns the nodes of one cycle in dependency order.
+fn topological_order(predecessors: &[Vec<usize>]) -> Result<Vec<usize>, Vec<usize>> {
+    let count = predecessors.len();
+    let mut remaining: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+    let mut done = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+
+    while let Some(next) = (0..count).find(|&index| !done[index] && remaining[index] == 0) {
+        done[next] = true;
+        order.push(next);
+
+        for (index, preceding) in predecessors.iter().enumerate() {
+            remaining[index] -= preceding.iter().filter(|&&predecessor| predecessor == next).count();
+        }
+    }
+
+    if order.len() == count {
+        return Ok(order);
+    }
+
+    // Every unsorted node still waits on another unsorted node, so walking backwards must loop
+    let mut path = vec![(0..count).find(|&index| !done[index]).unwrap()];
+    loop {
+        let current = *path.last().unwrap();
+        let previous = *predecessors[current].iter().find(|&&predecessor| !done[predecessor]).unwrap();
+
+        if let Some(start) = path.iter().position(|&index| index == previous) {
+            let mut cycle = path.split_off(start);
+            cycle.reverse();
+
+            let first = (0..cycle.len()).min_by_key(|&position| cycle[position]).unwrap();
+            cycle.rotate_left(first);
+            return Err(cycle);
+        }
+
+        path.push(previous);
+    }
+}
+
+/// For each node, which other nodes must run before it
+fn ancestors(order: &[usize], predecessors: &[Vec<usize>]) -> Vec<Vec<bool>> {
+    let count = predecessors.len();
+    let mut ancestors = vec![vec![false; count]; count];
+
+    for &index in order {
+        for &predecessor in &predecessors[index] {
+            let inherited = ancestors[predecessor].clone();
+            for (ancestor, inherited) in ancestors[index].iter_mut().zip(inherited) {
+                *ancestor |= inherited;
+            }
+
+            ancestors[index][predecessor] = true;
+        }
+    }
+
+    ancestors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use command_buffer::CommandBuffer;
+    use component::{Position, Velocity};
+    use query::{Changed, Join};
+    use storage::Storage;
+
+    use std::sync::{Arc, Mutex};
+
+    struct StubSystem(Access);
+
+    impl System for StubSystem {
+        fn access(&self) -> Access {
+            self.0.clone()
+        }
+
+        fn run(&mut self, _: &World, _: &Time, _: Tick) { }
+    }
+
+    fn stub() -> StubSystem {
+        StubSystem(Access::new())
+    }
+
+    #[test]
+    fn unconstrained_systems_keep_registration_order() {
+        let mut builder = Schedule::builder();
+        builder.add("first", stub());
+        builder.add("second", stub());
+        builder.add("third", stub());
+
+        assert_eq!(builder.build().unwrap().order(), ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn constraints_reorder_systems() {
+        let mut builder = Schedule::builder();
+        builder.add("render", stub()).after("physics");
+        builder.add("physics", stub()).after("input");
+        builder.add("input", stub()).before("render");
+
+        assert_eq!(builder.build().unwrap().order(), ["input", "physics", "render"]);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let mut builder = Schedule::builder();
+        builder.add("first", stub()).after("third");
+        builder.add("second", stub()).after("first");
+        builder.add("third", stub()).after("second");
+        builder.add("fourth", stub()).after("third");
+
+        assert_eq!(builder.build().err(), Some(ScheduleError::Cycle(vec![
+            "first".to_owned(), "second".to_owned(), "third".to_owned()
+        ])));
+    }
+
+    #[test]
+    fn unknown_dependencies_are_reported() {
+        let mut builder = Schedule::builder();
+        builder.add("first", stub()).before("missing");
+
+        assert_eq!(builder.build().err(), Some(ScheduleError::UnknownSystem {
+            system: "first".to_owned(),
+            dependency: "missing".to_owned(),
+        }));
+    }
+
+    #[test]
+    fn unordered_writers_are_reported() {
+        let mut builder = Schedule::builder();
+        builder.add("first", StubSystem(Access::new().write::<Position>()));
+        builder.add("second", StubSystem(Access::new().read::<Velocity>().write::<Position>()));
+
+        match builder.build().err() {
+            Some(ScheduleError::ConflictingWriters { first, second, .. }) => {
+                assert_eq!((first.as_str(), second.as_str()), ("first", "second"));
+            },
+            _ => panic!("expected conflicting writers"),
+        }
+    }
+
+    #[test]
+    fn transitively_ordered_writers_are_allowed() {
+        let mut builder = Schedule::builder();
+        builder.add("first", StubSystem(Access::new().write::<Position>()));
+        builder.add("middle", stub()).after("first");
+        builder.add("last", StubSystem(Access::new().write::<Position>())).after("middle");
+
+        assert_eq!(builder.build().unwrap().order(), ["first", "middle", "last"]);
+    }
+
+    #[test]
+    fn conflicting_systems_are_split_into_stages() {
+        let mut builder = Schedule::builder();
+        builder.add("keys", StubSystem(Access::new().write::<Velocity>()));
+        builder.add("command", stub());
+        builder.add("movement", StubSystem(Access::new().write::<Position>().read::<Velocity>())).after("keys");
+        builder.add("render", StubSystem(Access::new().read::<Position>()));
+        builder.add("audio", stub());
+
+        let schedule = builder.build().unwrap();
+        assert_eq!(schedule.stages(), vec![vec!["keys", "command", "audio"], vec!["movement"], vec!["render"]]);
+        assert_eq!(schedule.order(), ["keys", "command", "audio", "movement", "render"]);
+    }
+
+    struct MoveSystem(f64);
+
+    impl System for MoveSystem {
+        fn access(&self) -> Access {
+            Access::new().write::<Position>().read::<Velocity>()
+        }
+
+        fn run(&mut self, world: &World, _: &Time, _: Tick) {
+            for (_, (position, velocity)) in (&mut world.write::<Position>(), &world.read::<Velocity>()).join() {
+                position.0 = position.0 * self.0 + velocity.0;
+            }
+        }
+    }
+
+    struct AccelerateSystem;
+
+    impl System for AccelerateSystem {
+        fn access(&self) -> Access {
+            Access::new().write::<Velocity>()
+        }
+
+        fn run(&mut self, world: &World, _: &Time, _: Tick) {
+            for (_, (velocity,)) in (&mut world.write::<Velocity>(),).join() {
+                velocity.0 += 1.0;
+            }
+        }
+    }
+
+    struct AccelerateOnce(bool);
+
+    impl System for AccelerateOnce {
+        fn access(&self) -> Access {
+            AccelerateSystem.access()
+        }
+
+        fn run(&mut self, world: &World, time: &Time, last_run: Tick) {
+            if !self.0 {
+                self.0 = true;
+                AccelerateSystem.run(world, time, last_run);
+            }
+        }
+    }
+
+    struct CountChanged(Arc<Mutex<Vec<usize>>>);
+
+    impl System for CountChanged {
+        fn access(&self) -> Access {
+            Access::new().read::<Velocity>()
+        }
+
+        fn run(&mut self, world: &World, _: &Time, last_run: Tick) {
+            let changed = Changed(&world.read::<Velocity>(), last_run).join().count();
+            self.0.lock().unwrap().push(changed);
+        }
+    }
+
+    #[test]
+    fn systems_see_changes_since_their_last_run() {
+        let mut world = World::new();
+        world.register::<Velocity>();
+        world.create_entity().with_component(Velocity(0.0, 0.0)).build();
+        world.create_entity().with_component(Velocity(0.0, 0.0)).build();
+
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let mut builder = Schedule::builder();
+        builder.add("count", CountChanged(counts.clone()));
+        builder.add("accelerate", AccelerateOnce(false)).after("count");
+        let mut schedule = builder.build().unwrap();
+
+        for _ in 0..3 {
+            schedule.run(&mut world, &Time::new());
+        }
+
+        assert_eq!(*counts.lock().unwrap(), [2, 2, 0]);
+    }
+
+    fn run_with(executor: Executor) -> Vec<Option<Position>> {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        for index in 0..8 {
+            world.create_entity()
+                .with_component(Position(index as f64, 0.0))
+                .with_component(Velocity(1.0, 0.0))
+                .build();
+        }
+
+        let mut builder = Schedule::builder();
+        builder.add("accelerate", AccelerateSystem);
+        builder.add("double", MoveSystem(2.0)).after("accelerate");
+        builder.add("triple", MoveSystem(3.0)).after("double");
+        builder.add("idle", stub());
+
+        let mut schedule = builder.build().unwrap();
+        schedule.set_executor(executor);
+        for _ in 0..3 {
+            schedule.run(&mut world, &Time::new());
+        }
+
+        let positions = world.read::<Position>().as_slice().to_vec();
+        positions
+    }
+
+    #[test]
+    fn parallel_executor_matches_serial_executor() {
+        assert_eq!(run_with(Executor::Parallel { threads: 4 }), run_with(Executor::Serial));
+    }
+
+    struct SpawnSystem(CommandBuffer);
+
+    impl System for SpawnSystem {
+        fn access(&self) -> Access {
+            Access::new().read::<Position>()
+        }
+
+        fn run(&mut self, world: &World, _: &Time, _: Tick) {
+            let count = world.read::<Position>().len();
+            self.0.spawn(world).with_component(Position(count as f64, 0.0)).build();
+        }
+
+        fn apply(&mut self, world: &mut World) {
+            self.0.apply(world);
+        }
+    }
+
+    #[test]
+    fn deferred_changes_apply_between_stages() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Velocity>();
+        let mut builder = Schedule::builder();
+        builder.add("first", SpawnSystem(CommandBuffer::new()));
+        builder.add("second", SpawnSystem(CommandBuffer::new())).after("first");
+        builder.add("beside", SpawnSystem(CommandBuffer::new()));
+
+        let mut schedule = builder.build().unwrap();
+        assert_eq!(schedule.stages(), vec![vec!["first", "beside"], vec!["second"]]);
+        schedule.run(&mut world, &Time::new());
+
+        let positions = world.read::<Position>();
+        let mut xs: Vec<f64> = positions.entities().iter().map(|entity| positions.get(*entity).unwrap().0).collect();
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(xs, vec![0.0, 0.0, 2.0]);
+    }
+}
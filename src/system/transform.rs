@@ -0,0 +1,123 @@
+use hierarchy::{Children, GlobalTransform, LocalTransform, Parent, Transform};
+use query::{Join, Without};
+use storage::{Storage, StorageMut, Tick};
+use system::{Access, System};
+use time::Time;
+use world::World;
+
+/// Computes every `GlobalTransform` from the local transforms of its entity and its ancestors
+///
+/// Walks each hierarchy from its roots down, so parents are always updated before their
+/// children. A root is any entity without a `Parent` that has `Children` or a `LocalTransform`.
+/// An entity without a `LocalTransform` counts as the identity, passing its parent's transform
+/// on unchanged, and one without a `GlobalTransform` is not written to. A `GlobalTransform` is
+/// only written, and so only marked changed, when its value differs.
+pub struct TransformSystem;
+
+impl System for TransformSystem {
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<LocalTransform>()
+            .read::<Parent>()
+            .read::<Children>()
+            .write::<GlobalTransform>()
+    }
+
+    fn run(&mut self, world: &World, _: &Time, _: Tick) {
+        let (locals, parents, children) = (world.read::<LocalTransform>(), world.read::<Parent>(), world.read::<Children>());
+        let mut globals = world.write::<GlobalTransform>();
+
+        let mut pending: Vec<_> = (&children, Without(&parents)).join()
+            .map(|(entity, _)| (entity, Transform::IDENTITY))
+            .collect();
+        pending.extend((&locals, Without(&parents), Without(&children)).join().map(|(entity, _)| (entity, Transform::IDENTITY)));
+
+        while let Some((entity, parent)) = pending.pop() {
+            let global = match locals.get(entity) {
+                Some(local) => parent.then(&local.0),
+                None => parent,
+            };
+
+            if globals.get(entity).is_some_and(|current| current.0 != global) {
+                globals.get_mut(entity).unwrap().0 = global;
+            }
+
+            if let Some(children) = children.get(entity) {
+                pending.extend(children.as_slice().iter().map(|child| (*child, global)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::Entity;
+
+    fn spawn(world: &mut World, x: f64) -> Entity {
+        world.create_entity()
+            .with_component(LocalTransform(Transform::from_translation(x, 0.0)))
+            .with_component(GlobalTransform::default())
+            .build()
+    }
+
+    #[test]
+    fn global_transforms_propagate_from_roots() {
+        let mut world = World::new();
+        world.register::<LocalTransform>();
+        world.register::<GlobalTransform>();
+        let root = spawn(&mut world, 1.0);
+        let child = spawn(&mut world, 2.0);
+        let grandchild = spawn(&mut world, 3.0);
+        let bare = world.create_entity().with_component(GlobalTransform::default()).build();
+        world.set_parent(grandchild, child);
+        world.set_parent(child, root);
+        world.set_parent(bare, grandchild);
+
+        world.write::<LocalTransform>().get_mut(root).unwrap().0.scale = 2.0;
+        TransformSystem.run(&world, &Time::new(), 0);
+
+        let globals = world.read::<GlobalTransform>();
+        assert_eq!(globals.get(root).unwrap().0.translation, (1.0, 0.0));
+        assert_eq!(globals.get(child).unwrap().0.translation, (5.0, 0.0));
+        assert_eq!(globals.get(grandchild).unwrap().0.translation, (11.0, 0.0));
+        assert_eq!(globals.get(bare).unwrap().0, globals.get(grandchild).unwrap().0);
+    }
+
+    #[test]
+    fn roots_without_a_local_transform_count_as_the_identity() {
+        let mut world = World::new();
+        world.register::<LocalTransform>();
+        world.register::<GlobalTransform>();
+        let group = world.create_entity().with_component(GlobalTransform(Transform::from_translation(7.0, 7.0))).build();
+        let member = spawn(&mut world, 2.0);
+        world.set_parent(member, group);
+
+        TransformSystem.run(&world, &Time::new(), 0);
+
+        let globals = world.read::<GlobalTransform>();
+        assert_eq!(globals.get(group).unwrap().0, Transform::IDENTITY);
+        assert_eq!(globals.get(member).unwrap().0.translation, (2.0, 0.0));
+    }
+
+    #[test]
+    fn unchanged_transforms_are_not_marked_changed() {
+        use query::Changed;
+
+        let mut world = World::new();
+        world.register::<LocalTransform>();
+        world.register::<GlobalTransform>();
+        let root = spawn(&mut world, 1.0);
+        let child = spawn(&mut world, 2.0);
+        world.set_parent(child, root);
+
+        TransformSystem.run(&world, &Time::new(), 0);
+        let last_run = world.change_tick();
+        world.advance_change_tick();
+        TransformSystem.run(&world, &Time::new(), last_run);
+
+        let globals = world.read::<GlobalTransform>();
+        assert_eq!(Changed(&globals, last_run).join().count(), 0);
+        assert_eq!(globals.get(child).unwrap().0.translation, (3.0, 0.0));
+    }
+}
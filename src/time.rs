@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+/// The simulation clock handed to every system
+///
+/// Deltas are scaled by the time scale, so systems that move things by `delta_seconds_f64`
+/// slow down, speed up or pause together.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    frame_count: u64,
+    time_scale: f64,
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self {
+            delta: Duration::new(0, 0),
+            elapsed: Duration::new(0, 0),
+            frame_count: 0,
+            time_scale: 1.0,
+        }
+    }
+
+    /// Moves the clock forward by a frame lasting `real_delta` before scaling
+    pub fn advance(&mut self, real_delta: Duration) {
+        self.delta = real_delta.mul_f64(self.time_scale);
+        self.elapsed += self.delta;
+        self.frame_count += 1;
+    }
+
+    /// The scaled time since the previous frame
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds_f64(&self) -> f64 {
+        self.delta.as_secs_f64()
+    }
+
+    /// The scaled time since the clock started
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds_f64(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Sets how fast simulated time passes relative to real time
+    ///
+    /// A scale of zero pauses the simulation. Negative scales are treated as zero.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.time_scale == 0.0
+    }
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advancing_keeps_sub_second_precision() {
+        let mut time = Time::new();
+        time.advance(Duration::from_millis(16));
+        time.advance(Duration::from_millis(16));
+
+        assert_eq!(time.delta_seconds_f64(), 0.016);
+        assert_eq!(time.elapsed(), Duration::from_millis(32));
+        assert_eq!(time.frame_count(), 2);
+    }
+
+    #[test]
+    fn time_scale_applies_to_deltas() {
+        let mut time = Time::new();
+        time.set_time_scale(0.5);
+        time.advance(Duration::from_millis(20));
+
+        assert_eq!(time.delta(), Duration::from_millis(10));
+        assert_eq!(time.elapsed(), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn zero_time_scale_pauses() {
+        let mut time = Time::new();
+        time.set_time_scale(-1.0);
+        time.advance(Duration::from_millis(20));
+
+        assert!(time.is_paused());
+        assert_eq!(time.delta_seconds_f64(), 0.0);
+        assert_eq!(time.frame_count(), 1);
+    }
+}
@@ -1,34 +1,172 @@
-/// The underlying storage type for `BitVector`
-pub type BitVectorStorage = u128;
+use std::ops::Index;
 
-/// A 128-bit unsigned integer-backed bit vector
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct BitVector(BitVectorStorage);
+/// The word type `BitVector` keeps its bits in
+pub type BitVectorStorage = u64;
+
+const WORD_BITS: usize = 64;
+
+/// A growable set of bits, each standing for a key, command, component type or similar
+///
+/// Bits past the end of the vector read as unset, and setting one grows the vector to fit, so
+/// no index is ever out of bounds. Trailing zero words are dropped, which keeps equal sets equal
+/// whatever their history.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitVector(Vec<BitVectorStorage>);
 
 impl BitVector {
     pub fn new() -> Self {
-        BitVector(0)
+        BitVector(Vec::new())
+    }
+
+    /// Creates an empty vector with room for `bits` bits before it reallocates
+    pub fn with_capacity(bits: usize) -> Self {
+        BitVector(Vec::with_capacity(words_for(bits)))
+    }
+
+    pub fn is_set<T: Into<usize>>(&self, bit: T) -> bool {
+        let bit = bit.into();
+        match self.0.get(bit / WORD_BITS) {
+            Some(word) => word & mask(bit) != 0,
+            None => false,
+        }
+    }
+
+    pub fn set<T: Into<usize>>(&mut self, bit: T) {
+        let bit = bit.into();
+        if self.0.len() <= bit / WORD_BITS {
+            self.0.resize(bit / WORD_BITS + 1, 0);
+        }
+
+        self.0[bit / WORD_BITS] |= mask(bit);
+    }
+
+    pub fn unset<T: Into<usize>>(&mut self, bit: T) {
+        let bit = bit.into();
+        if let Some(word) = self.0.get_mut(bit / WORD_BITS) {
+            *word &= !mask(bit);
+            self.trim();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
     }
 
-    pub fn is_set<T: Into<BitVectorStorage>>(&self, bit: T) -> bool {
-        self.0 & 1 << bit.into() != 0
+    /// The indices of the set bits, in increasing order
+    pub fn iter(&self) -> Ones<'_> {
+        Ones { words: &self.0, index: 0, word: self.0.first().cloned().unwrap_or(0) }
     }
 
-    pub fn set<T: Into<BitVectorStorage>>(&mut self, bit: T) {
-        self.0 |= 1 << bit.into()
+    /// The bits set in either vector
+    pub fn union(&self, other: &BitVector) -> BitVector {
+        self.combine(other, |a, b| a | b)
     }
 
-    pub fn unset<T: Into<BitVectorStorage>>(&mut self, bit: T) {
-        self.0 &= !(1 << bit.into())
+    /// The bits set in both vectors
+    pub fn intersection(&self, other: &BitVector) -> BitVector {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// The bits set here but not in `other`
+    pub fn difference(&self, other: &BitVector) -> BitVector {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// The bits set in exactly one of the vectors
+    pub fn xor(&self, other: &BitVector) -> BitVector {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Whether every bit set here is also set in `other`
+    pub fn is_subset(&self, other: &BitVector) -> bool {
+        self.0.iter().enumerate().all(|(index, word)| word & !other.word(index) == 0)
+    }
+
+    /// Whether no bit is set in both vectors
+    pub fn is_disjoint(&self, other: &BitVector) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & b == 0)
+    }
+
+    fn word(&self, index: usize) -> BitVectorStorage {
+        self.0.get(index).cloned().unwrap_or(0)
+    }
+
+    fn combine<F: Fn(BitVectorStorage, BitVectorStorage) -> BitVectorStorage>(&self, other: &BitVector, op: F) -> BitVector {
+        let words = self.0.len().max(other.0.len());
+        let mut result = BitVector((0..words).map(|index| op(self.word(index), other.word(index))).collect());
+        result.trim();
+        result
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
     }
 }
 
-impl From<BitVectorStorage> for BitVector {
-    fn from(value: BitVectorStorage) -> Self {
-        BitVector(value)
+/// Reads a bit as `vector[bit]`, which is `false` past the end of the vector
+impl Index<usize> for BitVector {
+    type Output = bool;
+
+    fn index(&self, bit: usize) -> &bool {
+        if self.is_set(bit) { &true } else { &false }
     }
 }
 
+/// Creates a vector holding the bits of a 128-bit integer
+impl From<u128> for BitVector {
+    fn from(value: u128) -> Self {
+        let mut vector = BitVector(vec![value as BitVectorStorage, (value >> WORD_BITS) as BitVectorStorage]);
+        vector.trim();
+        vector
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVector {
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+
+    fn into_iter(self) -> Ones<'a> {
+        self.iter()
+    }
+}
+
+/// Iterates the indices of the set bits of a `BitVector`
+pub struct Ones<'a> {
+    words: &'a [BitVectorStorage],
+    index: usize,
+    word: BitVectorStorage,
+}
+
+impl<'a> Iterator for Ones<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.index += 1;
+            self.word = *self.words.get(self.index)?;
+        }
+
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.index * WORD_BITS + bit)
+    }
+}
+
+fn mask(bit: usize) -> BitVectorStorage {
+    1 << (bit % WORD_BITS)
+}
+
+fn words_for(bits: usize) -> usize {
+    bits.div_ceil(WORD_BITS)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,6 +174,7 @@ mod tests {
     #[test]
     fn initialize_to_empty() {
         assert_eq!(BitVector::new(), 0.into());
+        assert!(BitVector::with_capacity(300).is_empty());
     }
 
     #[test]
@@ -72,4 +211,50 @@ mod tests {
         assert!(!vec.is_set(1_u8));
         assert!(vec.is_set(2_u8));
     }
+
+    #[test]
+    fn compare_sets() {
+        let small: BitVector = 0b0101.into();
+        let large: BitVector = 0b1101.into();
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(small.is_disjoint(&0b1010.into()));
+        assert_eq!(small.union(&0b10.into()), 0b0111.into());
+    }
+
+    #[test]
+    fn bits_past_the_end_are_unset_until_set() {
+        let mut vec = BitVector::new();
+        assert!(!vec[1000]);
+        vec.unset(1000_usize);
+
+        vec.set(200_usize);
+        assert!(vec[200] && !vec[199] && !vec[1000]);
+
+        vec.unset(200_usize);
+        assert_eq!(vec, BitVector::new());
+    }
+
+    #[test]
+    fn set_operations_span_words() {
+        let mut a = BitVector::new();
+        let mut b = BitVector::new();
+        for bit in &[1_usize, 64, 130] {
+            a.set(*bit);
+        }
+        for bit in &[64_usize, 300] {
+            b.set(*bit);
+        }
+
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), [1, 64, 130, 300]);
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), [64]);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), [1, 130]);
+        assert_eq!(a.xor(&b).iter().collect::<Vec<_>>(), [1, 130, 300]);
+        assert_eq!(b.difference(&a.union(&b)), BitVector::new());
+        assert_eq!(a.union(&b).count_ones(), 4);
+        assert!(a.intersection(&b).is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(!a.is_disjoint(&b));
+    }
 }
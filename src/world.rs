@@ -1,41 +1,500 @@
 use component::Component;
-use storage::map::MapStorage;
-use storage::sequence::SequenceStorage;
+use entity::{Entity, EntityAllocator};
+use event::{Event, EventWriter, Events};
+use hierarchy::{Children, Parent};
+use query::{ComponentSet, Mask};
+use replay::{StateHash, StateHasher};
+use resource::{Res, ResMut, Resource, ResourceCell};
+use storage::{ReadStorage, Storage, StorageCell, StorageMut, Tick, WriteStorage};
+use storage::archetype::{ArchetypeId, Archetypes};
+use util::BitVector;
 
-pub struct World {
-    pub commands: MapStorage,
-    pub keys: MapStorage,
-    pub positions: SequenceStorage,
-    pub velocities: SequenceStorage,
+use std::any::{self, TypeId};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Where a `World` keeps its components
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// Each component type in its own `Component::Storage`
+    PerComponent,
+
+    /// Every component type in `ArchetypeStorage` tables, packed by the set of components each
+    /// entity has
+    Archetype,
+}
 
-    player_id: Option<usize>,
+pub struct World {
+    backend: StorageBackend,
+    archetypes: Archetypes,
+    storages: HashMap<TypeId, StorageCell>,
+    component_bits: HashMap<TypeId, usize>,
+    signatures: Vec<Option<(Entity, BitVector)>>,
+    resources: HashMap<TypeId, ResourceCell>,
+    entities: EntityAllocator,
+    player_id: Option<Entity>,
+    state_hashers: Vec<(TypeId, StateHashFn)>,
+    event_updaters: Vec<(TypeId, EventUpdateFn)>,
+    change_tick: Tick,
 }
 
 impl World {
+    /// Creates a world without any components registered or resources inserted
+    ///
+    /// The `DefaultPlugin` adds the built-in ones when the world is given to an `App`.
     pub fn new() -> Self {
-        Self {
-            commands: MapStorage::new(),
-            keys: MapStorage::new(),
-            positions: SequenceStorage::new(),
-            velocities: SequenceStorage::new(),
+        Self::with_backend(StorageBackend::PerComponent)
+    }
 
+    /// Creates a world like `new` that keeps its components in the given backend
+    pub fn with_backend(backend: StorageBackend) -> Self {
+        Self {
+            backend,
+            archetypes: Archetypes::new(),
+            storages: HashMap::new(),
+            component_bits: HashMap::new(),
+            signatures: Vec::new(),
+            resources: HashMap::new(),
+            entities: EntityAllocator::new(),
             player_id: None,
+            state_hashers: Vec::new(),
+            event_updaters: Vec::new(),
+            change_tick: 1,
+        }
+    }
+
+    /// Creates an empty storage for components of type `T` and gives it a signature bit
+    ///
+    /// Registering a type more than once has no effect.
+    pub fn register<T: Component>(&mut self) {
+        if self.is_registered::<T>() {
+            return;
+        }
+
+        let bit = self.storages.len();
+        let cell = match self.backend {
+            StorageBackend::PerComponent => StorageCell::new::<T>(),
+            StorageBackend::Archetype => StorageCell::archetype::<T>(),
+        };
+
+        self.storages.insert(TypeId::of::<T>(), cell);
+        self.component_bits.insert(TypeId::of::<T>(), bit);
+    }
+
+    /// The bit standing for `T` in entity signatures
+    ///
+    /// Panics if `T` is not registered.
+    pub fn component_bit<T: Component>(&self) -> usize {
+        match self.component_bits.get(&TypeId::of::<T>()) {
+            Some(bit) => *bit,
+            None => panic!("`{}` is not a registered component", any::type_name::<T>()),
+        }
+    }
+
+    /// The component types a live entity has, as a `BitVector` of their `component_bit`s
+    ///
+    /// Kept up to date by `EntityBuilder`, `CommandBuffer`, `insert_component`,
+    /// `remove_component` and `destroy_entity`, the only ways to attach or detach a component.
+    pub fn signature(&self, entity: Entity) -> Option<&BitVector> {
+        match self.signatures.get(entity.index()) {
+            Some(Some((alive, signature))) if *alive == entity => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// A mask matching entities with every type in `R` and none in `E`
+    ///
+    /// Panics if a type is not registered.
+    pub fn mask<R: ComponentSet, E: ComponentSet>(&self) -> Mask {
+        Mask { required: R::signature(self), excluded: E::signature(self) }
+    }
+
+    /// The live entities whose signatures match a mask, found without touching any storage
+    ///
+    /// Each entity is listed at most once, since every slot holds one signature.
+    pub fn matching(&self, mask: &Mask) -> Vec<Entity> {
+        self.signatures.iter()
+            .filter_map(|slot| match slot {
+                Some((entity, signature)) if mask.matches(signature) => Some(*entity),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn backend(&self) -> StorageBackend {
+        self.backend
+    }
+
+    /// The archetypes of the entities, when the backend is `StorageBackend::Archetype`
+    pub fn archetypes(&self) -> &Archetypes {
+        &self.archetypes
+    }
+
+    /// Includes components of type `T` in `state_hash`, registering `T` if needed
+    pub fn track_state<T: Component + StateHash>(&mut self) {
+        fn hash_storage<T: Component + StateHash>(world: &World, hasher: &mut StateHasher) {
+            let storage = world.read::<T>();
+            let mut entities = storage.entities();
+            entities.sort();
+
+            for entity in entities {
+                entity.hash(hasher);
+                storage.get(entity).unwrap().hash_state(hasher);
+            }
+        }
+
+        self.register::<T>();
+        if self.state_hashers.iter().all(|(id, _)| *id != TypeId::of::<T>()) {
+            self.state_hashers.push((TypeId::of::<T>(), hash_storage::<T>));
+        }
+    }
+
+    /// Hashes every tracked component, so that replays can check they reproduce a run exactly
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        for (index, (_, hash)) in self.state_hashers.iter().enumerate() {
+            hasher.write_usize(index);
+            hash(self, &mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    pub fn is_registered<T: Component>(&self) -> bool {
+        self.storages.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Borrows the storage of `T` for reading
+    ///
+    /// Panics if `T` is not registered or its storage is already borrowed for writing.
+    pub fn read<T: Component>(&self) -> ReadStorage<'_, T> {
+        self.cell::<T>().read()
+    }
+
+    /// Borrows the storage of `T` for writing
+    ///
+    /// Panics if `T` is not registered or its storage is already borrowed.
+    pub fn write<T: Component>(&self) -> WriteStorage<'_, T> {
+        self.cell::<T>().write(self.change_tick)
+    }
+
+    /// Stores a singleton, returning the one of the same type it replaces
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> Option<R> {
+        self.resources.insert(TypeId::of::<R>(), ResourceCell::new(resource)).map(ResourceCell::into_inner)
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self) -> Option<R> {
+        self.resources.remove(&TypeId::of::<R>()).map(ResourceCell::into_inner)
+    }
+
+    pub fn has_resource<R: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// Borrows a resource for reading
+    ///
+    /// Panics if the resource was never inserted or is already borrowed for writing.
+    pub fn resource<R: Resource>(&self) -> Res<'_, R> {
+        self.resource_cell::<R>().read()
+    }
+
+    /// Borrows a resource for writing
+    ///
+    /// Panics if the resource was never inserted or is already borrowed.
+    pub fn resource_mut<R: Resource>(&self) -> ResMut<'_, R> {
+        self.resource_cell::<R>().write()
+    }
+
+    /// The tick recorded for components inserted or changed now
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick
+    }
+
+    /// Moves on to a new tick, so later changes can be told apart from earlier ones
+    pub fn advance_change_tick(&mut self) -> Tick {
+        self.change_tick += 1;
+        self.change_tick
+    }
+
+    /// Inserts an empty `Events<T>` resource that `update_events` starts a new frame of
+    ///
+    /// Adding an event type more than once has no effect.
+    pub fn add_event<T: Event>(&mut self) {
+        fn update<T: Event>(world: &World) {
+            world.resource_mut::<Events<T>>().update();
+        }
+
+        if self.event_updaters.iter().all(|(id, _)| *id != TypeId::of::<T>()) {
+            self.insert_resource(Events::<T>::new());
+            self.event_updaters.push((TypeId::of::<T>(), update::<T>));
         }
     }
 
-    pub fn create_entity(&mut self) -> EntityBuilder {
+    /// Borrows the queue of `T` events for sending
+    ///
+    /// Panics if `T` was not added with `add_event` or its queue is already borrowed.
+    pub fn event_writer<T: Event>(&self) -> EventWriter<'_, T> {
+        EventWriter::new(self.resource_mut::<Events<T>>())
+    }
+
+    /// Starts a new frame of every event queue, dropping events sent two frames ago
+    pub fn update_events(&mut self) {
+        for (_, update) in &self.event_updaters {
+            update(self);
+        }
+    }
+
+    pub fn create_entity(&mut self) -> EntityBuilder<'_> {
         EntityBuilder::new(self)
     }
 
-    pub fn player_id(&self) -> Option<usize> {
+    /// Hands out an entity that can be referred to before it is spawned, as by a `CommandBuffer`
+    ///
+    /// The entity comes alive, without any components, the next time the world creates,
+    /// destroys or changes the components of an entity.
+    pub fn reserve_entity(&self) -> Entity {
+        self.entities.reserve()
+    }
+
+    /// Removes an entity, its components and all of its descendants, freeing their slots for reuse
+    ///
+    /// Returns `false` if the entity was already destroyed.
+    pub fn destroy_entity(&mut self, entity: Entity) -> bool {
+        self.flush_entities();
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        self.remove_parent(entity);
+
+        let mut pending = vec![entity];
+        while let Some(entity) = pending.pop() {
+            pending.extend(self.children(entity));
+            self.entities.deallocate(entity);
+
+            for storage in self.storages.values_mut() {
+                storage.remove_entity(entity);
+            }
+
+            self.archetypes.remove(entity);
+            self.signatures[entity.index()] = None;
+
+            if self.player_id == Some(entity) {
+                self.player_id = None;
+            }
+        }
+
+        true
+    }
+
+    /// Attaches a component to a live entity, replacing any of the same type
+    ///
+    /// Panics if `T` is not registered.
+    pub fn insert_component<T: Component>(&mut self, entity: Entity, component: T) {
+        self.flush_entities();
+        if !self.is_alive(entity) {
+            return;
+        }
+
+        match self.backend {
+            StorageBackend::PerComponent => self.write::<T>().insert(entity, component),
+            StorageBackend::Archetype => {
+                let archetype = self.archetypes.with(self.archetypes.archetype_of(entity), TypeId::of::<T>());
+                self.move_to_archetype(entity, archetype);
+                self.write::<T>().insert_into(entity, archetype, component);
+            },
+        }
+
+        let bit = self.component_bit::<T>();
+        if let Some((_, signature)) = self.signatures[entity.index()].as_mut() {
+            signature.set(bit);
+        }
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        self.flush_entities();
+        let component = match self.backend {
+            StorageBackend::PerComponent => self.write::<T>().remove(entity)?,
+            StorageBackend::Archetype => {
+                let component = self.write::<T>().take(entity)?;
+                let archetype = self.archetypes.without(self.archetypes.archetype_of(entity), TypeId::of::<T>());
+                self.move_to_archetype(entity, archetype);
+                component
+            },
+        };
+
+        let bit = self.component_bit::<T>();
+        if let Some((_, signature)) = self.signatures[entity.index()].as_mut() {
+            signature.unset(bit);
+        }
+
+        Some(component)
+    }
+
+    /// Attaches `child` to `parent`, detaching it from any parent it had
+    ///
+    /// Returns `false` without changing anything if either entity is dead, or if `parent` is
+    /// `child` or one of its descendants.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        if !self.is_alive(child) || !self.is_alive(parent) {
+            return false;
+        }
+
+        self.register::<Parent>();
+        self.register::<Children>();
+
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            if entity == child {
+                return false;
+            }
+
+            ancestor = self.parent(entity);
+        }
+
+        self.remove_parent(child);
+        self.insert_component(child, Parent(parent));
+
+        let added = self.write::<Children>().get_mut(parent).map(|children| children.0.push(child)).is_some();
+        if !added {
+            self.insert_component(parent, Children(vec![child]));
+        }
+
+        true
+    }
+
+    /// Detaches an entity from its parent, returning the parent it had
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        if !self.is_registered::<Parent>() {
+            return None;
+        }
+
+        let parent = self.remove_component::<Parent>(child)?.0;
+
+        let now_empty = match self.write::<Children>().get_mut(parent) {
+            Some(children) => {
+                children.0.retain(|entity| *entity != child);
+                children.0.is_empty()
+            },
+            None => false,
+        };
+
+        if now_empty {
+            self.remove_component::<Children>(parent);
+        }
+
+        Some(parent)
+    }
+
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        if !self.is_registered::<Parent>() {
+            return None;
+        }
+
+        self.read::<Parent>().get(entity).map(Parent::entity)
+    }
+
+    /// The entities attached to an entity, in the order they were attached
+    pub fn children(&self, entity: Entity) -> Vec<Entity> {
+        if !self.is_registered::<Children>() {
+            return Vec::new();
+        }
+
+        self.read::<Children>().get(entity).map(|children| children.0.clone()).unwrap_or_default()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn player_id(&self) -> Option<Entity> {
         self.player_id
     }
+
+    /// Moves every component of an entity to the tables of its new archetype
+    fn move_to_archetype(&mut self, entity: Entity, archetype: ArchetypeId) {
+        let current = self.archetypes.archetype_of(entity);
+        if current == archetype {
+            return;
+        }
+
+        for id in self.archetypes.signature(current).to_vec() {
+            self.storages.get_mut(&id).unwrap().relocate(entity, archetype);
+        }
+
+        self.archetypes.set(entity, archetype);
+    }
+
+    fn cell<T: Component>(&self) -> &StorageCell {
+        match self.storages.get(&TypeId::of::<T>()) {
+            Some(cell) => cell,
+            None => panic!("`{}` is not a registered component", any::type_name::<T>()),
+        }
+    }
+
+    fn resource_cell<R: Resource>(&self) -> &ResourceCell {
+        match self.resources.get(&TypeId::of::<R>()) {
+            Some(cell) => cell,
+            None => panic!("`{}` is not an inserted resource", any::type_name::<R>()),
+        }
+    }
+
+    /// Creates an entity from the parts collected by an `EntityBuilder`
+    pub(crate) fn spawn(&mut self, components: Vec<ComponentInsert>, is_player: bool) -> Entity {
+        self.flush_entities();
+        let entity = self.entities.allocate();
+        self.track_entity(entity);
+        self.fill(entity, components, is_player);
+        entity
+    }
+
+    /// Gives a reserved entity the parts collected by a `CommandBuffer`, unless it was destroyed
+    pub(crate) fn spawn_reserved(&mut self, entity: Entity, components: Vec<ComponentInsert>, is_player: bool) {
+        self.flush_entities();
+        if self.is_alive(entity) {
+            self.fill(entity, components, is_player);
+        }
+    }
+
+    /// Brings reserved entities to life with empty signatures
+    fn flush_entities(&mut self) {
+        for entity in self.entities.flush() {
+            self.track_entity(entity);
+        }
+    }
+
+    fn track_entity(&mut self, entity: Entity) {
+        if self.signatures.len() <= entity.index() {
+            self.signatures.resize(entity.index() + 1, None);
+        }
+
+        self.signatures[entity.index()] = Some((entity, BitVector::new()));
+    }
+
+    fn fill(&mut self, entity: Entity, components: Vec<ComponentInsert>, is_player: bool) {
+        for insert in components {
+            insert(self, entity);
+        }
+
+        if is_player {
+            self.player_id = Some(entity);
+        }
+    }
 }
 
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) type ComponentInsert = Box<dyn FnOnce(&mut World, Entity) + Send>;
+type StateHashFn = fn(&World, &mut StateHasher);
+type EventUpdateFn = fn(&World);
+
 pub struct EntityBuilder<'a> {
     world: &'a mut World,
-    components: Vec<Component>,
-    index: usize,
+    components: Vec<ComponentInsert>,
     is_player: bool,
 }
 
@@ -44,19 +503,13 @@ impl<'a> EntityBuilder<'a> {
         Self {
             world,
             components: Vec::new(),
-            index: 0,
             is_player: false,
         }
     }
 
-    pub fn with_component(mut self, component: Component) -> Self {
-        match component {
-            Component::Position(_, _) => self.index = self.index.max(self.world.positions.size()),
-            Component::Velocity(_, _) => self.index = self.index.max(self.world.velocities.size()),
-            _ => { },
-        }
-
-        self.components.push(component);
+    /// Adds a component of any registered type to the entity
+    pub fn with_component<T: Component>(mut self, component: T) -> Self {
+        self.components.push(Box::new(move |world: &mut World, entity| world.insert_component(entity, component)));
         self
     }
 
@@ -65,78 +518,317 @@ impl<'a> EntityBuilder<'a> {
         self
     }
 
-    pub fn build(self) {
-        for component in self.components.into_iter() {
-            match component {
-                Component::Position(_, _) => self.world.positions.add(self.index, component),
-                Component::Velocity(_, _) => self.world.velocities.add(self.index, component),
-                Component::KeysPressed(_) => self.world.keys.add(self.index, component),
-                Component::Commands(_) => self.world.commands.add(self.index, component),
-                _ => { },
-            }
-        }
-
-        if self.is_player {
-            self.world.player_id = Some(self.index);
-        }
+    pub fn build(self) -> Entity {
+        self.world.spawn(self.components, self.is_player)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use component::{Position, Velocity};
+    use storage::Storage;
+    use storage::map::MapStorage;
+
+    fn tracked_world() -> World {
+        let mut world = World::new();
+        world.track_state::<Position>();
+        world.track_state::<Velocity>();
+        world
+    }
 
     #[test]
     fn create_entity_with_single_component() {
-        let mut world = World::new();
-        world.create_entity().with_component(Component::Position(1.0, 0.0)).build();
+        let mut world = tracked_world();
+        let entity = world.create_entity().with_component(Position(1.0, 0.0)).build();
+        assert_eq!(entity, Entity::new(0, 0));
         assert_eq!(world.player_id, None);
-        assert_eq!(world.positions.as_slice(), [Component::Position(1.0, 0.0)]);
+        assert_eq!(world.read::<Position>().as_slice(), [Some(Position(1.0, 0.0))]);
     }
 
     #[test]
     fn create_entity_with_multiple_components_with_aligned_storage() {
-        let mut world = World::new();
+        let mut world = tracked_world();
         world.create_entity()
-            .with_component(Component::Velocity(2.0, 0.0))
-            .with_component(Component::Position(5.0, 0.0))
+            .with_component(Velocity(2.0, 0.0))
+            .with_component(Position(5.0, 0.0))
             .build();
 
         assert_eq!(world.player_id, None);
-        assert_eq!(world.velocities.as_slice(), [Component::Velocity(2.0, 0.0)]);
-        assert_eq!(world.positions.as_slice(), [Component::Position(5.0, 0.0)]);
+        assert_eq!(world.read::<Velocity>().as_slice(), [Some(Velocity(2.0, 0.0))]);
+        assert_eq!(world.read::<Position>().as_slice(), [Some(Position(5.0, 0.0))]);
     }
 
     #[test]
     fn create_entity_with_multiple_components_with_unaligned_storage() {
-        let mut world = World::new();
-        world.positions.add(0, Component::Position(10.0, 10.0));
+        let mut world = tracked_world();
+        world.create_entity().with_component(Position(10.0, 10.0)).build();
         world.create_entity()
-            .with_component(Component::Velocity(2.0, 0.0))
-            .with_component(Component::Position(5.0, 0.0))
+            .with_component(Velocity(2.0, 0.0))
+            .with_component(Position(5.0, 0.0))
             .build();
 
         assert_eq!(world.player_id, None);
-        assert_eq!(world.velocities.as_slice(), [Component::Empty, Component::Velocity(2.0, 0.0)]);
-        assert_eq!(world.positions.as_slice(), [
-            Component::Position(10.0, 10.0), Component::Position(5.0, 0.0)
+        assert_eq!(world.read::<Velocity>().as_slice(), [None, Some(Velocity(2.0, 0.0))]);
+        assert_eq!(world.read::<Position>().as_slice(), [
+            Some(Position(10.0, 10.0)), Some(Position(5.0, 0.0))
         ]);
     }
 
     #[test]
     fn create_player_entity() {
-        let mut world = World::new();
-        world.positions.add(0, Component::Position(10.0, 10.0));
-        world.create_entity()
-            .with_component(Component::Velocity(2.0, 0.0))
-            .with_component(Component::Position(5.0, 0.0))
+        let mut world = tracked_world();
+        world.create_entity().with_component(Position(10.0, 10.0)).build();
+        let player = world.create_entity()
+            .with_component(Velocity(2.0, 0.0))
+            .with_component(Position(5.0, 0.0))
             .make_player()
             .build();
 
-        assert_eq!(world.player_id, Some(1));
-        assert_eq!(world.velocities.as_slice(), [Component::Empty, Component::Velocity(2.0, 0.0)]);
-        assert_eq!(world.positions.as_slice(), [
-            Component::Position(10.0, 10.0), Component::Position(5.0, 0.0)
+        assert_eq!(world.player_id, Some(player));
+        assert_eq!(player, Entity::new(1, 0));
+        assert_eq!(world.read::<Velocity>().as_slice(), [None, Some(Velocity(2.0, 0.0))]);
+        assert_eq!(world.read::<Position>().as_slice(), [
+            Some(Position(10.0, 10.0)), Some(Position(5.0, 0.0))
         ]);
     }
+
+    #[test]
+    fn components_are_looked_up_by_entity() {
+        let mut world = tracked_world();
+        let entity = world.create_entity().with_component(Position(1.0, 2.0)).build();
+
+        assert!(world.is_alive(entity));
+        assert_eq!(world.read::<Position>().get(entity), Some(&Position(1.0, 2.0)));
+        assert_eq!(world.read::<Position>().get(Entity::new(0, 1)), None);
+    }
+
+    #[test]
+    fn destroy_entity_clears_its_components() {
+        let mut world = tracked_world();
+        let first = world.create_entity()
+            .with_component(Position(1.0, 1.0))
+            .with_component(Velocity(1.0, 1.0))
+            .make_player()
+            .build();
+        let second = world.create_entity().with_component(Position(2.0, 2.0)).build();
+
+        assert!(world.destroy_entity(first));
+        assert!(!world.destroy_entity(first));
+        assert!(!world.is_alive(first));
+        assert!(world.is_alive(second));
+        assert_eq!(world.player_id, None);
+        assert_eq!(world.read::<Position>().as_slice(), [None, Some(Position(2.0, 2.0))]);
+        assert_eq!(world.read::<Velocity>().get(first), None);
+    }
+
+    #[test]
+    fn destroyed_slot_is_reused_by_builder() {
+        let mut world = tracked_world();
+        let old = world.create_entity().with_component(Position(1.0, 1.0)).build();
+        world.destroy_entity(old);
+
+        let new = world.create_entity().with_component(Position(3.0, 3.0)).build();
+        assert_eq!(new, Entity::new(0, 1));
+        assert_eq!(world.read::<Position>().get(old), None);
+        assert_eq!(world.read::<Position>().get(new), Some(&Position(3.0, 3.0)));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = MapStorage<Self>;
+    }
+
+    #[test]
+    fn signatures_follow_component_changes() {
+        let mut world = tracked_world();
+        world.register::<Health>();
+        let entity = world.create_entity().with_component(Position(0.0, 0.0)).with_component(Health(3)).build();
+        let (position, health) = (world.component_bit::<Position>(), world.component_bit::<Health>());
+
+        let signature = world.signature(entity).unwrap();
+        assert_eq!(signature.iter().collect::<Vec<_>>(), [position, health]);
+        assert!(!signature[world.component_bit::<Velocity>()]);
+
+        world.remove_component::<Health>(entity);
+        assert!(!world.signature(entity).unwrap()[health]);
+
+        world.destroy_entity(entity);
+        assert_eq!(world.signature(entity), None);
+
+        let reused = world.create_entity().build();
+        assert_eq!(world.signature(reused), Some(&BitVector::new()));
+    }
+
+    #[test]
+    fn parents_cannot_be_their_own_descendants() {
+        let mut world = tracked_world();
+        let (root, child, other) = (world.create_entity().build(), world.create_entity().build(), world.create_entity().build());
+
+        assert!(world.set_parent(child, root));
+        assert!(!world.set_parent(root, child));
+        assert!(!world.set_parent(root, root));
+        assert_eq!(world.children(root), [child]);
+
+        assert!(world.set_parent(child, other));
+        assert_eq!(world.parent(child), Some(other));
+        assert!(world.children(root).is_empty());
+        assert!(!world.read::<Children>().contains(root));
+    }
+
+    #[test]
+    fn destroying_a_parent_destroys_its_descendants() {
+        let mut world = tracked_world();
+        let root = world.create_entity().build();
+        let child = world.create_entity().with_component(Position(1.0, 1.0)).build();
+        let grandchild = world.create_entity().build();
+        let sibling = world.create_entity().build();
+        world.set_parent(child, root);
+        world.set_parent(grandchild, child);
+        world.set_parent(sibling, root);
+
+        assert!(world.destroy_entity(child));
+        assert!(!world.is_alive(grandchild));
+        assert_eq!(world.children(root), [sibling]);
+        assert!(world.read::<Position>().is_empty());
+
+        assert!(world.destroy_entity(root));
+        assert!(!world.is_alive(sibling));
+        assert!(world.read::<Parent>().is_empty() && world.read::<Children>().is_empty());
+    }
+
+    #[test]
+    fn deep_hierarchies_are_destroyed_without_recursing() {
+        let mut world = tracked_world();
+        world.register::<Parent>();
+        world.register::<Children>();
+        let root = world.create_entity().build();
+        let mut leaf = root;
+        for _ in 0..100_000 {
+            let child = world.create_entity().build();
+            world.insert_component(child, Parent(leaf));
+            world.insert_component(leaf, Children(vec![child]));
+            leaf = child;
+        }
+
+        assert!(world.destroy_entity(root));
+        assert!(!world.is_alive(leaf));
+        assert!(world.read::<Parent>().is_empty() && world.read::<Children>().is_empty());
+    }
+
+    #[test]
+    fn user_components_can_be_registered() {
+        let mut world = tracked_world();
+        assert!(!world.is_registered::<Health>());
+
+        world.register::<Health>();
+        let entity = world.create_entity().with_component(Health(10)).build();
+
+        assert_eq!(world.read::<Health>().get(entity), Some(&Health(10)));
+        assert_eq!(world.remove_component::<Health>(entity), Some(Health(10)));
+        assert!(world.read::<Health>().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn conflicting_borrows_panic() {
+        let world = tracked_world();
+        let _positions = world.read::<Position>();
+        world.write::<Position>();
+    }
+
+    #[test]
+    fn resources_can_be_inserted_read_and_mutated() {
+        let mut world = tracked_world();
+        assert!(!world.has_resource::<Health>());
+
+        assert_eq!(world.insert_resource(Health(3)), None);
+        world.resource_mut::<Health>().0 += 1;
+        assert_eq!(*world.resource::<Health>(), Health(4));
+
+        assert_eq!(world.insert_resource(Health(1)), Some(Health(4)));
+        assert_eq!(world.remove_resource::<Health>(), Some(Health(1)));
+        assert!(!world.has_resource::<Health>());
+    }
+
+    #[test]
+    #[should_panic(expected = "not an inserted resource")]
+    fn missing_resources_panic() {
+        tracked_world().resource::<Health>();
+    }
+
+    #[test]
+    fn state_hash_follows_tracked_components() {
+        let mut world = tracked_world();
+        let entity = world.create_entity().with_component(Position(1.0, 2.0)).build();
+        let hash = world.state_hash();
+        assert_eq!(tracked_world().state_hash(), tracked_world().state_hash());
+        assert_ne!(hash, tracked_world().state_hash());
+
+        world.register::<Health>();
+        world.insert_component(entity, Health(3));
+        assert_eq!(world.state_hash(), hash);
+
+        world.insert_component(entity, Position(1.0, 2.5));
+        assert_ne!(world.state_hash(), hash);
+    }
+
+    #[test]
+    fn added_events_are_updated_by_the_world() {
+        use event::EventReader;
+
+        let mut world = tracked_world();
+        world.add_event::<Health>();
+        world.add_event::<Health>();
+
+        world.event_writer::<Health>().send(Health(1));
+        world.update_events();
+        world.update_events();
+        world.event_writer::<Health>().send(Health(2));
+
+        let mut reader = EventReader::new();
+        assert_eq!(reader.read(&world.resource::<Events<Health>>()).collect::<Vec<_>>(), vec![&Health(2)]);
+    }
+
+    #[test]
+    fn components_record_the_tick_they_change_at() {
+        let mut world = tracked_world();
+        let entity = world.create_entity().with_component(Position(0.0, 0.0)).build();
+        let inserted = world.change_tick();
+
+        let changed = world.advance_change_tick();
+        world.write::<Position>().get_mut(entity).unwrap().0 = 1.0;
+
+        let positions = world.read::<Position>();
+        assert_eq!(positions.change_ticks().added(entity), Some(inserted));
+        assert_eq!(positions.change_ticks().changed(entity), Some(changed));
+    }
+
+    #[test]
+    fn archetype_worlds_move_entities_between_tables() {
+        use storage::archetype::ArchetypeId;
+
+        let mut world = World::with_backend(StorageBackend::Archetype);
+        world.track_state::<Position>();
+        world.track_state::<Velocity>();
+        world.register::<Health>();
+        let moving = world.create_entity().with_component(Position(1.0, 0.0)).with_component(Velocity(2.0, 0.0)).build();
+        let still = world.create_entity().with_component(Position(3.0, 0.0)).build();
+        assert_ne!(world.archetypes().archetype_of(moving), world.archetypes().archetype_of(still));
+
+        assert_eq!(world.remove_component::<Velocity>(moving), Some(Velocity(2.0, 0.0)));
+        assert_eq!(world.archetypes().archetype_of(moving), world.archetypes().archetype_of(still));
+        assert_eq!(world.read::<Position>().get(moving), Some(&Position(1.0, 0.0)));
+
+        world.insert_component(still, Health(5));
+        world.insert_component(still, Health(4));
+        assert_eq!(world.read::<Health>().get(still), Some(&Health(4)));
+        assert_eq!(world.read::<Position>().len(), 2);
+
+        world.destroy_entity(still);
+        assert_eq!(world.archetypes().archetype_of(still), ArchetypeId::EMPTY);
+        assert_eq!(world.read::<Position>().entities(), vec![moving]);
+    }
 }